@@ -0,0 +1,42 @@
+use std::{env, path::PathBuf, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=ANICARGO_GIT_SHA");
+
+    let version = env::var("CARGO_PKG_VERSION").unwrap_or_default();
+    let git_sha = env::var("ANICARGO_GIT_SHA")
+        .ok()
+        .map(|sha| sha.trim().to_owned())
+        .filter(|sha| !sha.is_empty())
+        .or_else(git_head_sha);
+
+    match git_sha {
+        Some(sha) => {
+            println!("cargo:rustc-env=ANICARGO_GIT_SHA={sha}");
+            println!("cargo:rustc-env=ANICARGO_LONG_VERSION={version} ({sha})");
+        }
+        None => println!("cargo:rustc-env=ANICARGO_LONG_VERSION={version}"),
+    }
+}
+
+fn git_head_sha() -> Option<String> {
+    if let Some(git_dir) = git_output(&["rev-parse", "--git-dir"]) {
+        let git_dir = PathBuf::from(git_dir);
+        println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+        println!("cargo:rerun-if-changed={}", git_dir.join("refs").display());
+    }
+
+    git_output(&["rev-parse", "--short=12", "HEAD"])
+}
+
+fn git_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8(output.stdout).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
@@ -0,0 +1,9 @@
+UPDATE users
+SET username = lower(trim(username))
+WHERE username <> lower(trim(username))
+  AND NOT EXISTS (
+    SELECT 1
+    FROM users AS other
+    WHERE other.id <> users.id
+      AND lower(trim(other.username)) = lower(trim(users.username))
+);
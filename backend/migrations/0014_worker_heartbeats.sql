@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS worker_heartbeats (
+    worker_id TEXT PRIMARY KEY,
+    last_seen_at TEXT NOT NULL
+);
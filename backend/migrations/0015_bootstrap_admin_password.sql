@@ -0,0 +1 @@
+ALTER TABLE users ADD COLUMN bootstrap_password_hash TEXT;
@@ -0,0 +1 @@
+ALTER TABLE bangumi_subject_cache ADD COLUMN availability TEXT NOT NULL DEFAULT 'available';
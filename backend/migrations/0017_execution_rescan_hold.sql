@@ -0,0 +1 @@
+ALTER TABLE download_executions ADD COLUMN rescan_held_at TEXT;
@@ -0,0 +1,6 @@
+UPDATE users
+SET username = lower(trim(username))
+WHERE username <> lower(trim(username));
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_normalized_username
+    ON users(lower(trim(username)));
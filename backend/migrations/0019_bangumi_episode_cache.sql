@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS bangumi_episode_cache (
+    bangumi_subject_id INTEGER PRIMARY KEY,
+    episodes_json TEXT NOT NULL,
+    refreshed_at TEXT NOT NULL
+);
@@ -0,0 +1,6 @@
+ALTER TABLE download_executions ADD COLUMN completion_notified_at TEXT;
+
+UPDATE download_executions
+SET completion_notified_at = COALESCE(completed_at, updated_at)
+WHERE state IN ('seeding', 'completed')
+   OR completed_at IS NOT NULL;
@@ -1,6 +1,6 @@
-use std::{collections::HashSet, time::Duration};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
-use anicargo_metadata_parser::{FileRole, ParseResult, parse_release_name};
+use anicargo_metadata_parser::{FileRole, ParseResult, Parser};
 use anyhow::Context;
 use reqwest::{Client, StatusCode};
 use serde::Deserialize;
@@ -19,6 +19,7 @@ pub struct AnimeGardenClient {
     http: Client,
     page_size: usize,
     max_pages: usize,
+    parser: Arc<Parser>,
 }
 
 #[derive(Debug, Clone)]
@@ -63,7 +64,7 @@ pub struct AnimeGardenResource {
 }
 
 impl AnimeGardenClient {
-    pub fn new(config: &AnimeGardenConfig) -> anyhow::Result<Self> {
+    pub fn new(config: &AnimeGardenConfig, parser: Arc<Parser>) -> anyhow::Result<Self> {
         let http = Client::builder()
             .timeout(Duration::from_secs(config.request_timeout_secs))
             .build()
@@ -74,6 +75,7 @@ impl AnimeGardenClient {
             http,
             page_size: config.page_size.max(1),
             max_pages: config.max_pages.max(1),
+            parser,
         })
     }
 
@@ -269,7 +271,12 @@ impl AnimeGardenClient {
                 })?;
 
             let is_empty = payload.resources.is_empty();
-            merged.extend(payload.resources.into_iter().map(AnimeGardenResource::from));
+            merged.extend(
+                payload
+                    .resources
+                    .into_iter()
+                    .map(|resource| AnimeGardenResource::from_raw(resource, &self.parser)),
+            );
 
             if payload.pagination.complete || is_empty {
                 break;
@@ -686,6 +693,7 @@ fn build_slot_from_fields(
 }
 
 fn merge_release_slot(
+    parser: &Parser,
     title: &str,
     release_type: &str,
     provider_id: &str,
@@ -699,7 +707,7 @@ fn merge_release_slot(
 ) {
     let api_slot = api_release_slot(api_episode_number, api_episode_end_number);
     let parser_slot = manual_parse.and_then(parse_result_slot);
-    let fallback = infer_release_slot(title, release_type, provider_id, "airing");
+    let fallback = infer_release_slot(parser, title, release_type, provider_id, "airing");
     let merged = build_slot_from_fields(
         api_slot
             .as_ref()
@@ -717,8 +725,8 @@ fn merge_release_slot(
     (api_slot, parser_slot, merged)
 }
 
-impl From<ResourceRaw> for AnimeGardenResource {
-    fn from(value: ResourceRaw) -> Self {
+impl AnimeGardenResource {
+    fn from_raw(value: ResourceRaw, parser: &Parser) -> Self {
         let api_episode_number = value
             .metadata
             .as_ref()
@@ -737,8 +745,9 @@ impl From<ResourceRaw> for AnimeGardenResource {
             .and_then(|metadata| metadata.anipar.as_ref())
             .and_then(|parsed| parsed.episode_range.as_ref().map(|range| range.to))
             .or(api_episode_number);
-        let manual_parse = Some(parse_release_name(&value.title));
+        let manual_parse = Some(parser.parse_release_name(&value.title));
         let (api_release_slot, parser_release_slot, merged_release_slot) = merge_release_slot(
+            parser,
             &value.title,
             &value.release_type,
             &value.provider_id,
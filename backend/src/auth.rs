@@ -19,6 +19,12 @@ pub struct AdminIdentity {
     pub username: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct UserIdentity {
+    pub id: i64,
+    pub username: String,
+}
+
 pub fn hash_password(password: &str) -> Result<String, AppError> {
     let salt = SaltString::generate(&mut OsRng);
     Argon2::default()
@@ -37,6 +43,10 @@ pub fn verify_password(password_hash: &str, password: &str) -> bool {
         .is_ok()
 }
 
+pub fn normalize_username(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
 pub fn generate_token() -> String {
     Uuid::new_v4().simple().to_string()
 }
@@ -1,22 +1,84 @@
-use std::time::Duration;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::Context;
 use chrono::{Local, NaiveDate};
 use reqwest::{Client, Response, StatusCode};
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::{Map, Value, json};
+use tokio::time::{self, Instant};
 use tracing::warn;
 
 use crate::{
     config::BangumiConfig,
-    types::{AppError, EpisodeDto, InfoboxItemDto, SubjectCardDto, SubjectDetailDto},
+    matching::normalize_title,
+    types::{
+        AppError, EpisodeDto, InfoboxItemDto, SubjectCardDto, SubjectCastActorDto,
+        SubjectCharacterDto, SubjectDetailDto, SubjectPersonDto, SubjectType,
+    },
 };
 
+const DEFAULT_RATE_LIMIT_BACKOFF_SECS: u64 = 5;
+const MAX_RETRY_AFTER_SECS: u64 = 60;
+const EPISODE_PAGE_LIMIT: usize = 100;
+const EXACT_SEARCH_LIMIT: usize = 10;
+const MAX_EPISODE_PAGES: usize = 20;
+
+/// Cheap to clone and safe to share across tasks: clones reuse the same
+/// connection pool and request pacer, so build one per process and pass it around.
 #[derive(Clone)]
 pub struct BangumiClient {
     base_url: String,
     http: Client,
     user_agent: String,
+    pacer: Arc<RequestPacer>,
+}
+
+pub struct FetchedImage {
+    pub bytes: Vec<u8>,
+    /// Lowercased MIME type without parameters, empty when the host sent none.
+    pub content_type: String,
+}
+
+struct RequestPacer {
+    min_interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RequestPacer {
+    fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait_turn(&self) {
+        let slot = self.reserve_slot();
+        time::sleep_until(slot).await;
+    }
+
+    fn reserve_slot(&self) -> Instant {
+        let now = Instant::now();
+        let Ok(mut next_slot) = self.next_slot.lock() else {
+            return now;
+        };
+
+        let slot = (*next_slot).max(now);
+        *next_slot = slot + self.min_interval;
+        slot
+    }
+
+    fn defer_until(&self, until: Instant) {
+        if let Ok(mut next_slot) = self.next_slot.lock()
+            && until > *next_slot
+        {
+            *next_slot = until;
+        }
+    }
 }
 
 impl BangumiClient {
@@ -30,6 +92,9 @@ impl BangumiClient {
             base_url: config.base_url.trim_end_matches('/').to_owned(),
             http,
             user_agent: config.user_agent.clone(),
+            pacer: Arc::new(RequestPacer::new(Duration::from_millis(
+                config.min_request_interval_ms,
+            ))),
         })
     }
 
@@ -66,6 +131,32 @@ impl BangumiClient {
         })
     }
 
+    pub async fn find_exact(&self, name: &str) -> Result<Option<SubjectRaw>, AppError> {
+        let target = normalize_title(name);
+        if target.is_empty() {
+            return Ok(None);
+        }
+
+        let query = BangumiSearchQuery {
+            keyword: name.trim().to_owned(),
+            sort: "match".to_owned(),
+            tags: Vec::new(),
+            meta_tags: Vec::new(),
+            air_date_start: None,
+            air_date_end: None,
+            rating_min: None,
+            rating_max: None,
+            rating_count_min: None,
+            rating_count_max: None,
+            rank_min: None,
+            rank_max: None,
+            nsfw: None,
+        };
+        let response = self.search_subjects(&query, EXACT_SEARCH_LIMIT, 0).await?;
+
+        Ok(single_exact_match(response.data, &target))
+    }
+
     pub async fn fetch_subject(&self, subject_id: i64) -> Result<SubjectRaw, AppError> {
         let url = format!("{}/v0/subjects/{}", self.base_url, subject_id);
         let response = self
@@ -94,28 +185,29 @@ impl BangumiClient {
     }
 
     pub async fn fetch_episodes(&self, subject_id: i64) -> Result<Vec<EpisodeRaw>, AppError> {
-        let url = format!(
-            "{}/v0/episodes?subject_id={}&type=0",
-            self.base_url, subject_id
-        );
-        let response = self
-            .send_request(
-                self.http
-                    .get(&url)
-                    .header(reqwest::header::USER_AGENT, &self.user_agent),
-                "episode list",
-                &url,
-            )
-            .await?;
+        let mut episodes = Vec::new();
+        let mut offset = 0;
 
-        if !response.status().is_success() {
-            return Err(self.episodes_status_error(response, &url, subject_id).await);
-        }
+        for _ in 0..MAX_EPISODE_PAGES {
+            let url = format!(
+                "{}/v0/episodes?subject_id={}&type=0&limit={}&offset={}",
+                self.base_url, subject_id, EPISODE_PAGE_LIMIT, offset
+            );
+            let response = self
+                .send_request(
+                    self.http
+                        .get(&url)
+                        .header(reqwest::header::USER_AGENT, &self.user_agent),
+                    "episode list",
+                    &url,
+                )
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(self.episodes_status_error(response, &url, subject_id).await);
+            }
 
-        response
-            .json::<PagedEpisodesRaw>()
-            .await
-            .map_err(|error| {
+            let page = response.json::<PagedEpisodesRaw>().await.map_err(|error| {
                 warn!(
                     url = %url,
                     subject_id,
@@ -123,8 +215,23 @@ impl BangumiClient {
                     "Failed to parse Bangumi episode list response"
                 );
                 AppError::upstream("failed to parse Bangumi episode list")
-            })
-            .map(|payload| payload.data)
+            })?;
+
+            let page_len = page.data.len();
+            episodes.extend(page.data);
+
+            match next_episode_offset(offset, page_len, page.limit, page.total) {
+                Some(next) => offset = next,
+                None => return Ok(episodes),
+            }
+        }
+
+        warn!(
+            subject_id,
+            pages = MAX_EPISODE_PAGES,
+            "Bangumi episode list exceeded the page cap; returning a partial list"
+        );
+        Ok(episodes)
     }
 
     pub async fn fetch_related_subjects(
@@ -160,16 +267,165 @@ impl BangumiClient {
             })
     }
 
+    pub async fn fetch_subject_persons(
+        &self,
+        subject_id: i64,
+    ) -> Result<Vec<SubjectPersonRaw>, AppError> {
+        self.fetch_subject_list(subject_id, "persons", "subject persons")
+            .await
+    }
+
+    pub async fn fetch_subject_characters(
+        &self,
+        subject_id: i64,
+    ) -> Result<Vec<SubjectCharacterRaw>, AppError> {
+        self.fetch_subject_list(subject_id, "characters", "subject characters")
+            .await
+    }
+
+    async fn fetch_subject_list<T: DeserializeOwned>(
+        &self,
+        subject_id: i64,
+        path: &str,
+        label: &'static str,
+    ) -> Result<Vec<T>, AppError> {
+        let url = format!("{}/v0/subjects/{}/{}", self.base_url, subject_id, path);
+        let response = self
+            .send_request(
+                self.http
+                    .get(&url)
+                    .header(reqwest::header::USER_AGENT, &self.user_agent),
+                label,
+                &url,
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(self.subject_status_error(response, &url).await);
+        }
+
+        response.json::<Vec<T>>().await.map_err(|error| {
+            warn!(
+                url = %url,
+                subject_id,
+                error = %error,
+                "Failed to parse Bangumi {label} response"
+            );
+            AppError::upstream(format!("failed to parse Bangumi {label}"))
+        })
+    }
+
+    /// Downloads an image, refusing bodies larger than `max_bytes`.
+    pub async fn fetch_image(&self, url: &str, max_bytes: usize) -> Result<FetchedImage, AppError> {
+        let mut response = self
+            .http
+            .get(url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .send()
+            .await
+            .map_err(|error| {
+                warn!(url = %url, error = %error, "Failed to reach Bangumi image host");
+                AppError::upstream("failed to reach Bangumi image host")
+            })?;
+
+        if !response.status().is_success() {
+            let (status, body) = read_upstream_error(response).await;
+            warn!(
+                url = %url,
+                status = %status,
+                body = %body,
+                "Bangumi image host returned an unsuccessful response"
+            );
+            return Err(AppError::upstream("Bangumi image host returned an error"));
+        }
+
+        let too_large = || {
+            warn!(url = %url, max_bytes, "Bangumi image exceeds the size limit");
+            AppError::upstream("Bangumi image is too large")
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_bytes as u64)
+        {
+            return Err(too_large());
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| {
+                value
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .unwrap_or_default();
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|error| {
+            warn!(url = %url, error = %error, "Failed to read Bangumi image body");
+            AppError::upstream("failed to read Bangumi image")
+        })? {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(FetchedImage {
+            bytes,
+            content_type,
+        })
+    }
+
+    /// Sends a paced request. A 429 pushes the shared pacer back by the Retry-After delay and
+    /// the request is retried once in the deferred slot before the response goes to the caller.
     async fn send_request(
         &self,
         request: reqwest::RequestBuilder,
         action: &str,
         url: &str,
     ) -> Result<Response, AppError> {
-        request.send().await.map_err(|error| {
+        let retry = request.try_clone();
+        let response = self.send_paced(request, action, url).await?;
+
+        match retry {
+            Some(retry) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                self.send_paced(retry, action, url).await
+            }
+            _ => Ok(response),
+        }
+    }
+
+    async fn send_paced(
+        &self,
+        request: reqwest::RequestBuilder,
+        action: &str,
+        url: &str,
+    ) -> Result<Response, AppError> {
+        self.pacer.wait_turn().await;
+
+        let response = request.send().await.map_err(|error| {
             warn!(action, url = %url, error = %error, "Failed to reach Bangumi");
             AppError::upstream(format!("failed to reach Bangumi {action}"))
-        })
+        })?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let delay = parse_retry_after(&response)
+                .unwrap_or(Duration::from_secs(DEFAULT_RATE_LIMIT_BACKOFF_SECS));
+            warn!(
+                action,
+                url = %url,
+                delay_ms = delay.as_millis() as u64,
+                "Bangumi rate limited the client; deferring subsequent requests"
+            );
+            self.pacer.defer_until(Instant::now() + delay);
+        }
+
+        Ok(response)
     }
 
     async fn search_status_error(&self, response: Response, url: &str) -> AppError {
@@ -226,6 +482,60 @@ impl BangumiClient {
     }
 }
 
+/// Returns the only subject whose normalized name or Chinese name equals `target`, or `None`
+/// when nothing or more than one subject matches.
+fn single_exact_match(subjects: Vec<SubjectRaw>, target: &str) -> Option<SubjectRaw> {
+    let mut matches = subjects.into_iter().filter(|subject| {
+        normalize_title(&subject.name) == target || normalize_title(&subject.name_cn) == target
+    });
+    let first = matches.next();
+    if matches.next().is_some() {
+        return None;
+    }
+
+    first
+}
+
+fn next_episode_offset(
+    offset: usize,
+    page_len: usize,
+    limit: usize,
+    total: usize,
+) -> Option<usize> {
+    let limit = if limit == 0 {
+        EPISODE_PAGE_LIMIT
+    } else {
+        limit
+    };
+    if page_len == 0 || page_len < limit {
+        return None;
+    }
+
+    let next = offset + page_len;
+    (next < total).then_some(next)
+}
+
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    let seconds = match value.parse::<u64>() {
+        Ok(seconds) => seconds,
+        Err(_) => {
+            let retry_at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (retry_at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .num_seconds()
+                .max(0) as u64
+        }
+    };
+
+    Some(Duration::from_secs(seconds.min(MAX_RETRY_AFTER_SECS)))
+}
+
 async fn read_upstream_error(response: Response) -> (StatusCode, String) {
     let status = response.status();
     let body = response
@@ -266,7 +576,7 @@ pub struct BangumiSearchQuery {
 impl BangumiSearchQuery {
     fn to_payload(&self) -> Value {
         let mut filter = Map::new();
-        filter.insert("type".to_owned(), json!([2]));
+        filter.insert("type".to_owned(), json!([i64::from(SubjectType::Anime)]));
 
         if !self.tags.is_empty() {
             filter.insert("tag".to_owned(), json!(self.tags));
@@ -323,6 +633,8 @@ impl BangumiSearchQuery {
 #[derive(Debug, Clone, Deserialize)]
 pub struct SubjectRaw {
     pub id: i64,
+    #[serde(default, rename = "type")]
+    pub subject_type: Option<SubjectType>,
     pub name: String,
     #[serde(default)]
     pub name_cn: String,
@@ -346,7 +658,7 @@ pub struct SubjectRaw {
     pub rating: Option<RatingRaw>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EpisodeRaw {
     pub id: i64,
     #[serde(default)]
@@ -363,10 +675,74 @@ pub struct EpisodeRaw {
     pub duration_seconds: Option<i64>,
 }
 
+/// A Bangumi date kept at the precision it was given, so partial values such
+/// as `2025` or `2025-10` still carry their year and month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AirDate {
+    pub year: i32,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+}
+
+impl AirDate {
+    pub fn parse(value: &str) -> Option<Self> {
+        let date_part = value
+            .trim()
+            .split(|character: char| character == 'T' || character.is_whitespace())
+            .next()?;
+        let mut parts = date_part.split('-');
+
+        let year = parts.next()?;
+        if year.len() != 4 || !year.chars().all(|character| character.is_ascii_digit()) {
+            return None;
+        }
+        let year = year.parse::<i32>().ok()?;
+
+        let month = match parts.next() {
+            Some(month) => {
+                let month = month.parse::<u32>().ok()?;
+                if !(1..=12).contains(&month) {
+                    return None;
+                }
+                Some(month)
+            }
+            None => None,
+        };
+        let day = match parts.next() {
+            Some(day) => {
+                let day = day.parse::<u32>().ok()?;
+                NaiveDate::from_ymd_opt(year, month?, day)?;
+                Some(day)
+            }
+            None => None,
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self { year, month, day })
+    }
+
+    pub fn to_naive_date(self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year, self.month?, self.day?)
+    }
+}
+
+impl EpisodeRaw {
+    pub fn parsed_airdate(&self) -> Option<AirDate> {
+        AirDate::parse(&self.airdate)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct PagedEpisodesRaw {
     #[serde(default)]
     pub data: Vec<EpisodeRaw>,
+    #[serde(default)]
+    pub total: usize,
+    #[serde(default)]
+    pub limit: usize,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -400,7 +776,8 @@ pub struct InfoboxRaw {
 #[derive(Debug, Clone, Deserialize)]
 pub struct RelatedSubjectRaw {
     pub id: i64,
-    pub r#type: i64,
+    #[serde(rename = "type")]
+    pub subject_type: SubjectType,
     #[serde(default)]
     pub relation: String,
     pub name: String,
@@ -408,6 +785,87 @@ pub struct RelatedSubjectRaw {
     pub name_cn: String,
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PortraitImagesRaw {
+    #[serde(default)]
+    pub large: Option<String>,
+    #[serde(default)]
+    pub medium: Option<String>,
+    #[serde(default)]
+    pub grid: Option<String>,
+}
+
+impl PortraitImagesRaw {
+    fn preferred(&self) -> Option<String> {
+        [&self.medium, &self.grid, &self.large]
+            .into_iter()
+            .flatten()
+            .find(|url| !url.is_empty())
+            .cloned()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubjectPersonRaw {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub relation: String,
+    #[serde(default)]
+    pub career: Vec<String>,
+    #[serde(default)]
+    pub images: Option<PortraitImagesRaw>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubjectCharacterRaw {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub relation: String,
+    #[serde(default)]
+    pub images: Option<PortraitImagesRaw>,
+    #[serde(default)]
+    pub actors: Vec<CharacterActorRaw>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CharacterActorRaw {
+    pub id: i64,
+    pub name: String,
+}
+
+impl SubjectPersonRaw {
+    pub fn to_dto(&self) -> SubjectPersonDto {
+        SubjectPersonDto {
+            bangumi_person_id: self.id,
+            name: self.name.clone(),
+            relation: self.relation.clone(),
+            career: self.career.clone(),
+            image_url: self.images.as_ref().and_then(PortraitImagesRaw::preferred),
+        }
+    }
+}
+
+impl SubjectCharacterRaw {
+    pub fn to_dto(&self) -> SubjectCharacterDto {
+        SubjectCharacterDto {
+            bangumi_character_id: self.id,
+            name: self.name.clone(),
+            relation: self.relation.clone(),
+            image_url: self.images.as_ref().and_then(PortraitImagesRaw::preferred),
+            actors: self
+                .actors
+                .iter()
+                .map(|actor| SubjectCastActorDto {
+                    bangumi_person_id: actor.id,
+                    name: actor.name.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
 impl SubjectRaw {
     pub fn to_card(&self) -> SubjectCardDto {
         let mut card = self.base_card();
@@ -447,12 +905,14 @@ impl SubjectRaw {
             total_episodes: self.total_episodes,
             rating_score: self.rating.as_ref().and_then(|rating| rating.score),
             catalog_label: None,
+            unavailable: false,
         }
     }
 
     pub fn to_detail(&self) -> SubjectDetailDto {
         SubjectDetailDto {
             bangumi_subject_id: self.id,
+            subject_type: self.subject_type,
             title: self.name.clone(),
             title_cn: self.name_cn.clone(),
             summary: self.summary.clone(),
@@ -504,8 +964,16 @@ impl SubjectRaw {
         "completed"
     }
 
+    pub fn parsed_date(&self) -> Option<AirDate> {
+        self.air_date
+            .as_deref()
+            .or(self.date.as_deref())
+            .and_then(AirDate::parse)
+    }
+
     fn is_upcoming(&self) -> bool {
-        parse_subject_date(self.air_date.as_ref().or(self.date.as_ref()))
+        self.parsed_date()
+            .and_then(AirDate::to_naive_date)
             .is_some_and(|date| date > Local::now().date_naive())
     }
 
@@ -591,12 +1059,6 @@ fn flatten_infobox_value(value: &Value) -> String {
     }
 }
 
-fn parse_subject_date(value: Option<&String>) -> Option<NaiveDate> {
-    let date = value?;
-    let date_part = date.split_once('T').map(|(left, _)| left).unwrap_or(date);
-    NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()
-}
-
 fn build_range_filter<T, F>(
     min: Option<T>,
     max: Option<T>,
@@ -642,9 +1104,213 @@ fn trim_float(value: f64) -> String {
     text
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchFacets {
     pub years: Vec<i32>,
     pub tags: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AirDate, BangumiClient, RelatedSubjectRaw, SubjectRaw, next_episode_offset,
+        single_exact_match,
+    };
+    use crate::{config::AppConfig, types::SubjectType};
+    use axum::{
+        Json, Router,
+        extract::Query,
+        http::{StatusCode, header},
+        response::{IntoResponse, Response},
+        routing::get,
+    };
+    use serde_json::json;
+    use std::{
+        collections::HashMap,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    async fn stub_client(router: Router) -> BangumiClient {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind stub server");
+        let address = listener.local_addr().expect("stub address");
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.expect("serve stub");
+        });
+
+        let mut config = AppConfig::default().bangumi;
+        config.base_url = format!("http://{address}");
+        config.min_request_interval_ms = 0;
+        BangumiClient::new(&config).expect("client")
+    }
+
+    fn rate_limited() -> Response {
+        (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "0")]).into_response()
+    }
+
+    #[tokio::test]
+    async fn retries_once_after_rate_limit() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new().route(
+            "/v0/subjects/{id}",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        rate_limited()
+                    } else {
+                        Json(json!({ "id": 7, "name": "Sample" })).into_response()
+                    }
+                }
+            }),
+        );
+
+        let subject = stub_client(router)
+            .await
+            .fetch_subject(7)
+            .await
+            .expect("retried subject");
+        assert_eq!(subject.id, 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn repeated_rate_limit_is_returned_after_one_retry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new().route(
+            "/v0/subjects/{id}",
+            get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { rate_limited() }
+            }),
+        );
+
+        assert!(stub_client(router).await.fetch_subject(7).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn fetch_episodes_stops_on_short_page() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new().route(
+            "/v0/episodes",
+            get(move |Query(params): Query<HashMap<String, usize>>| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let offset = params.get("offset").copied().unwrap_or_default();
+                    let len = if offset == 0 { 100 } else { 30 };
+                    let data = (offset..offset + len)
+                        .map(|id| json!({ "id": id, "sort": id + 1 }))
+                        .collect::<Vec<_>>();
+                    Json(json!({ "data": data, "total": 500, "limit": 100 }))
+                }
+            }),
+        );
+
+        let episodes = stub_client(router)
+            .await
+            .fetch_episodes(7)
+            .await
+            .expect("episodes");
+        assert_eq!(episodes.len(), 130);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    fn subject(id: i64, name: &str, name_cn: &str) -> SubjectRaw {
+        serde_json::from_value(json!({ "id": id, "name": name, "name_cn": name_cn })).unwrap()
+    }
+
+    #[test]
+    fn exact_match_accepts_a_single_normalized_hit() {
+        let subjects = vec![
+            subject(1, "Sousou no Frieren", "葬送的芙莉莲"),
+            subject(2, "Sousou no Frieren: Marumaru", ""),
+        ];
+        let found = single_exact_match(subjects.clone(), "sousounofrieren").expect("match");
+        assert_eq!(found.id, 1);
+        assert_eq!(
+            single_exact_match(subjects, "葬送的芙莉莲").map(|subject| subject.id),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn exact_match_rejects_ambiguous_hits() {
+        let subjects = vec![
+            subject(1, "Hunter x Hunter", ""),
+            subject(2, "HUNTER×HUNTER", "Hunter x Hunter"),
+        ];
+        assert!(single_exact_match(subjects, "hunterxhunter").is_none());
+    }
+
+    #[test]
+    fn exact_match_returns_none_without_hits() {
+        let subjects = vec![subject(1, "Spy x Family", "间谍过家家")];
+        assert!(single_exact_match(subjects, "frieren").is_none());
+        assert!(single_exact_match(Vec::new(), "frieren").is_none());
+    }
+
+    #[test]
+    fn stops_on_short_final_page() {
+        assert_eq!(next_episode_offset(0, 100, 100, 130), Some(100));
+        assert_eq!(next_episode_offset(100, 30, 100, 130), None);
+    }
+
+    #[test]
+    fn stops_when_total_is_missing_or_reached() {
+        assert_eq!(next_episode_offset(0, 100, 100, 0), None);
+        assert_eq!(next_episode_offset(0, 100, 100, 100), None);
+        assert_eq!(next_episode_offset(0, 0, 100, 500), None);
+    }
+
+    #[test]
+    fn maps_subject_type_codes() {
+        let related: RelatedSubjectRaw = serde_json::from_value(json!({
+            "id": 1,
+            "type": 2,
+            "name": "Sample"
+        }))
+        .unwrap();
+        assert_eq!(related.subject_type, SubjectType::Anime);
+
+        assert_eq!(SubjectType::from(5), SubjectType::Unknown(5));
+        assert_eq!(i64::from(SubjectType::Real), 6);
+        assert_eq!(json!(SubjectType::Game), json!("game"));
+    }
+
+    #[test]
+    fn parses_partial_air_dates() {
+        let full = AirDate::parse("2025-10-03T00:00:00+09:00").unwrap();
+        assert_eq!((full.year, full.month, full.day), (2025, Some(10), Some(3)));
+        assert!(full.to_naive_date().is_some());
+
+        let month = AirDate::parse("2025-10").unwrap();
+        assert_eq!((month.year, month.month, month.day), (2025, Some(10), None));
+        assert_eq!(month.to_naive_date(), None);
+
+        let year = AirDate::parse(" 2025 ").unwrap();
+        assert_eq!((year.year, year.month, year.day), (2025, None, None));
+    }
+
+    #[test]
+    fn rejects_malformed_air_dates() {
+        for value in [
+            "",
+            "25-10-03",
+            "2025-13",
+            "2025-02-30",
+            "2025-10-03-01",
+            "soon",
+        ] {
+            assert_eq!(AirDate::parse(value), None, "{value}");
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! Version details embedded at compile time by `build.rs`.
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const LONG_VERSION: &str = env!("ANICARGO_LONG_VERSION");
+pub const GIT_SHA: Option<&str> = option_env!("ANICARGO_GIT_SHA");
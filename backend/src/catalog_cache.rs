@@ -8,10 +8,11 @@ use chrono::Utc;
 use futures::stream::{self, StreamExt};
 use regex::Regex;
 use sqlx::{FromRow, SqlitePool};
-use tracing::warn;
+use tracing::{Instrument, Span, debug_span, field, info, instrument, warn};
 
 use crate::{
-    bangumi::{BangumiClient, BangumiSearchQuery, SubjectRaw},
+    bangumi::{BangumiClient, BangumiSearchQuery, EpisodeRaw, SubjectRaw},
+    matching::{self, normalize_title, score_text_pair},
     season_catalog::derive_release_status,
     types::{
         AppError, CatalogManifestResponse, CatalogPageResponse, CatalogSectionDto, SubjectCardDto,
@@ -23,6 +24,7 @@ const CATALOG_REFRESH_TTL_HOURS: i64 = 12;
 const MATCH_CONCURRENCY: usize = 6;
 const STATUS_REFRESH_CONCURRENCY: usize = 6;
 const INITIAL_STATUS_REFRESH_AT: &str = "1970-01-01T00:00:00Z";
+pub const SUBJECT_UNAVAILABLE: &str = "unavailable";
 
 #[derive(Debug, Clone, Copy)]
 enum CatalogKind {
@@ -100,6 +102,45 @@ struct StatusRefreshCandidateRow {
     status_refreshed_at: Option<String>,
 }
 
+#[derive(Debug, Clone, FromRow)]
+struct CachedSubjectRow {
+    bangumi_subject_id: i64,
+    title: String,
+    title_cn: String,
+    summary: String,
+    air_date: Option<String>,
+    air_weekday: Option<i64>,
+    total_episodes: Option<i64>,
+    image_portrait: Option<String>,
+    image_banner: Option<String>,
+    tags_json: String,
+    rating_score: Option<f64>,
+    release_status: String,
+    availability: String,
+}
+
+impl CachedSubjectRow {
+    fn to_card(&self) -> SubjectCardDto {
+        SubjectCardDto {
+            bangumi_subject_id: self.bangumi_subject_id,
+            title: self.title.clone(),
+            title_cn: self.title_cn.clone(),
+            summary: self.summary.clone(),
+            release_status: self.release_status.clone(),
+            air_date: self.air_date.clone(),
+            broadcast_time: None,
+            air_weekday: self.air_weekday.and_then(|value| u8::try_from(value).ok()),
+            image_portrait: self.image_portrait.clone(),
+            image_banner: self.image_banner.clone(),
+            tags: parse_tags_json(&self.tags_json).unwrap_or_default(),
+            total_episodes: self.total_episodes,
+            rating_score: self.rating_score,
+            catalog_label: None,
+            unavailable: self.availability == SUBJECT_UNAVAILABLE,
+        }
+    }
+}
+
 #[derive(Debug, Clone, FromRow)]
 struct CatalogPageRow {
     entry_id: i64,
@@ -123,6 +164,7 @@ struct CatalogPageRow {
     total_episodes: Option<i64>,
     rating_score: Option<f64>,
     release_status: Option<String>,
+    availability: Option<String>,
 }
 
 impl CatalogPageRow {
@@ -182,6 +224,7 @@ impl CatalogPageRow {
             total_episodes: self.total_episodes,
             rating_score: if matched { self.rating_score } else { None },
             catalog_label: self.catalog_label.clone(),
+            unavailable: self.availability.as_deref() == Some(SUBJECT_UNAVAILABLE),
         }
     }
 }
@@ -259,6 +302,144 @@ pub async fn load_catalog_page(
     })
 }
 
+pub async fn load_subject_card(
+    pool: &SqlitePool,
+    bangumi: &BangumiClient,
+    subject_id: i64,
+) -> Result<SubjectCardDto, AppError> {
+    let cached = sqlx::query_as::<_, CachedSubjectRow>(
+        "SELECT
+            bangumi_subject_id,
+            title,
+            title_cn,
+            summary,
+            air_date,
+            air_weekday,
+            total_episodes,
+            image_portrait,
+            image_banner,
+            tags_json,
+            rating_score,
+            release_status,
+            availability
+         FROM bangumi_subject_cache
+         WHERE bangumi_subject_id = ?1
+         LIMIT 1",
+    )
+    .bind(subject_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to read cached Bangumi subject"))?;
+
+    if let Some(row) = cached {
+        return Ok(row.to_card());
+    }
+
+    let (card, _) = refresh_subject_card(pool, bangumi, subject_id).await?;
+    Ok(card)
+}
+
+/// Returns the subject's episode list from `bangumi_episode_cache`, refetching it from Bangumi
+/// once the row is older than the catalog TTL. A stale row is still served if Bangumi fails.
+pub async fn load_subject_episodes(
+    pool: &SqlitePool,
+    bangumi: &BangumiClient,
+    subject_id: i64,
+) -> Result<Vec<EpisodeRaw>, AppError> {
+    let cached = sqlx::query_as::<_, (String, String)>(
+        "SELECT episodes_json, refreshed_at
+         FROM bangumi_episode_cache
+         WHERE bangumi_subject_id = ?1
+         LIMIT 1",
+    )
+    .bind(subject_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to read cached Bangumi episodes"))?
+    .and_then(|(episodes_json, refreshed_at)| {
+        serde_json::from_str::<Vec<EpisodeRaw>>(&episodes_json)
+            .ok()
+            .map(|episodes| (episodes, refreshed_at))
+    });
+
+    if let Some((episodes, refreshed_at)) = &cached
+        && !is_stale_rfc3339(refreshed_at, CATALOG_REFRESH_TTL_HOURS)
+    {
+        return Ok(episodes.clone());
+    }
+
+    match bangumi.fetch_episodes(subject_id).await {
+        Ok(episodes) => {
+            store_subject_episodes(pool, subject_id, &episodes, &now_string()).await?;
+            Ok(episodes)
+        }
+        Err(error) => match cached {
+            Some((episodes, _)) => {
+                warn!(
+                    subject_id,
+                    error = %error,
+                    "Failed to refresh Bangumi episodes; serving the stale cached list"
+                );
+                Ok(episodes)
+            }
+            None => Err(error),
+        },
+    }
+}
+
+async fn store_subject_episodes(
+    pool: &SqlitePool,
+    subject_id: i64,
+    episodes: &[EpisodeRaw],
+    refreshed_at: &str,
+) -> Result<(), AppError> {
+    let episodes_json = serde_json::to_string(episodes)
+        .map_err(|_| AppError::internal("failed to serialize Bangumi episodes"))?;
+
+    sqlx::query(
+        "INSERT INTO bangumi_episode_cache (bangumi_subject_id, episodes_json, refreshed_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(bangumi_subject_id) DO UPDATE SET
+            episodes_json = excluded.episodes_json,
+            refreshed_at = excluded.refreshed_at",
+    )
+    .bind(subject_id)
+    .bind(episodes_json)
+    .bind(refreshed_at)
+    .execute(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to store cached Bangumi episodes"))?;
+
+    Ok(())
+}
+
+#[instrument(skip(pool, bangumi), fields(episodes = field::Empty))]
+pub async fn refresh_subject_card(
+    pool: &SqlitePool,
+    bangumi: &BangumiClient,
+    subject_id: i64,
+) -> Result<(SubjectCardDto, usize), AppError> {
+    let (subject, episodes) = match tokio::try_join!(
+        bangumi.fetch_subject(subject_id),
+        bangumi.fetch_episodes(subject_id)
+    ) {
+        Ok(fetched) => fetched,
+        Err(error @ AppError::NotFound(_)) => {
+            mark_subject_unavailable(pool, subject_id, &now_string()).await?;
+            return Err(error);
+        }
+        Err(error) => return Err(error),
+    };
+    let mut card = subject.to_card();
+    card.release_status = derive_release_status(&subject, &episodes).to_owned();
+
+    let refreshed_at = now_string();
+    upsert_subject_cache(pool, &card, &refreshed_at, &refreshed_at).await?;
+    store_subject_episodes(pool, subject_id, &episodes, &refreshed_at).await?;
+    Span::current().record("episodes", episodes.len());
+    Ok((card, episodes.len()))
+}
+
 async fn sync_catalog(
     yuc: &YucClient,
     pool: &SqlitePool,
@@ -553,7 +734,8 @@ async fn load_catalog_rows(
             bangumi_subject_cache.tags_json,
             bangumi_subject_cache.total_episodes,
             bangumi_subject_cache.rating_score,
-            bangumi_subject_cache.release_status
+            bangumi_subject_cache.release_status,
+            bangumi_subject_cache.availability
          FROM yuc_catalog_entries
          INNER JOIN yuc_catalogs ON yuc_catalogs.id = yuc_catalog_entries.yuc_catalog_id
          LEFT JOIN bangumi_subject_cache
@@ -658,6 +840,7 @@ async fn populate_missing_matches(
     Ok(())
 }
 
+#[instrument(skip(pool, bangumi), fields(due = field::Empty))]
 async fn refresh_subject_statuses(
     pool: &SqlitePool,
     bangumi: &BangumiClient,
@@ -685,6 +868,7 @@ async fn refresh_subject_statuses(
         .map(|row| row.bangumi_subject_id)
         .collect::<Vec<_>>();
 
+    Span::current().record("due", subject_ids.len());
     if subject_ids.is_empty() {
         return Ok(());
     }
@@ -709,7 +893,15 @@ async fn refresh_subject_statuses(
 
                     let mut card = subject.to_card();
                     card.release_status = derive_release_status(&subject, &episodes).to_owned();
-                    Some(card)
+                    Some((subject_id, Some(card)))
+                }
+                Err(error @ AppError::NotFound(_)) => {
+                    warn!(
+                        subject_id,
+                        error = %error,
+                        "Bangumi subject is gone; marking the cached catalog entry unavailable"
+                    );
+                    Some((subject_id, None))
                 }
                 Err(error) => {
                     warn!(
@@ -721,15 +913,46 @@ async fn refresh_subject_statuses(
                 }
             }
         }
+        .instrument(debug_span!("refresh_subject_status", subject_id))
     }))
     .buffer_unordered(STATUS_REFRESH_CONCURRENCY)
     .filter_map(|item| async move { item })
     .collect::<Vec<_>>()
     .await;
 
-    for card in cards {
-        upsert_subject_cache(pool, &card, &refreshed_at, &refreshed_at).await?;
+    let refreshed = cards.len();
+    for (subject_id, card) in cards {
+        match card {
+            Some(card) => upsert_subject_cache(pool, &card, &refreshed_at, &refreshed_at).await?,
+            None => mark_subject_unavailable(pool, subject_id, &refreshed_at).await?,
+        }
     }
+    info!(
+        refreshed,
+        "Refreshed Bangumi subject statuses for cached catalog"
+    );
+
+    Ok(())
+}
+
+/// Keeps the cached row for a subject Bangumi no longer serves but flags it, so
+/// cards stay renderable and the next status refresh waits for its normal turn.
+pub async fn mark_subject_unavailable(
+    pool: &SqlitePool,
+    subject_id: i64,
+    refreshed_at: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE bangumi_subject_cache
+         SET availability = ?1, status_refreshed_at = ?2
+         WHERE bangumi_subject_id = ?3",
+    )
+    .bind(SUBJECT_UNAVAILABLE)
+    .bind(refreshed_at)
+    .bind(subject_id)
+    .execute(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to mark Bangumi subject unavailable"))?;
 
     Ok(())
 }
@@ -758,8 +981,9 @@ async fn upsert_subject_cache(
             rating_score,
             release_status,
             metadata_refreshed_at,
-            status_refreshed_at
-         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            status_refreshed_at,
+            availability
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, 'available')
          ON CONFLICT(bangumi_subject_id) DO UPDATE SET
             title = excluded.title,
             title_cn = excluded.title_cn,
@@ -773,7 +997,8 @@ async fn upsert_subject_cache(
             rating_score = excluded.rating_score,
             release_status = excluded.release_status,
             metadata_refreshed_at = excluded.metadata_refreshed_at,
-            status_refreshed_at = excluded.status_refreshed_at",
+            status_refreshed_at = excluded.status_refreshed_at,
+            availability = excluded.availability",
     )
     .bind(card.bangumi_subject_id)
     .bind(&card.title)
@@ -935,21 +1160,11 @@ fn adjust_score_for_air_date(
     let Some(entry_date) = parse_catalog_air_date(entry) else {
         return base_score;
     };
-    let Some(subject_date) =
-        parse_subject_date(subject.air_date.as_ref().or(subject.date.as_ref()))
-    else {
+    let Some(subject_date) = subject.parsed_date() else {
         return base_score;
     };
 
-    let delta_days = (subject_date - entry_date).num_days().abs();
-    match delta_days {
-        0 => base_score + 84.0,
-        1..=3 => base_score + 56.0,
-        4..=10 => base_score + 28.0,
-        11..=21 => base_score + 8.0,
-        22..=45 => (base_score - 18.0).max(0.0),
-        _ => (base_score - 72.0).max(0.0),
-    }
+    matching::adjust_score_for_air_date(base_score, entry_date, subject_date)
 }
 
 fn parse_catalog_air_date(entry: &CatalogMatchRow) -> Option<chrono::NaiveDate> {
@@ -969,12 +1184,6 @@ fn parse_catalog_air_date(entry: &CatalogMatchRow) -> Option<chrono::NaiveDate>
     chrono::NaiveDate::from_ymd_opt(year, month, day)
 }
 
-fn parse_subject_date(value: Option<&String>) -> Option<chrono::NaiveDate> {
-    let value = value?;
-    let prefix = value.get(0..10)?;
-    chrono::NaiveDate::parse_from_str(prefix, "%Y-%m-%d").ok()
-}
-
 fn preferred_subject_title(subject: &SubjectRaw) -> String {
     if !subject.name_cn.trim().is_empty() {
         subject.name_cn.clone()
@@ -983,46 +1192,6 @@ fn preferred_subject_title(subject: &SubjectRaw) -> String {
     }
 }
 
-fn score_text_pair(
-    left_normalized: &str,
-    left_stripped: &str,
-    right_normalized: &str,
-    right_stripped: &str,
-) -> i32 {
-    let mut score = 0;
-
-    if !left_normalized.is_empty() && left_normalized == right_normalized {
-        score = score.max(140);
-    }
-
-    if !left_stripped.is_empty() && left_stripped == right_stripped {
-        score = score.max(136);
-    }
-
-    if !left_stripped.is_empty()
-        && !right_stripped.is_empty()
-        && (left_normalized.contains(right_stripped)
-            || right_normalized.contains(left_stripped)
-            || left_stripped.contains(right_stripped)
-            || right_stripped.contains(left_stripped))
-    {
-        score = score.max(108);
-    }
-
-    score = score.max((dice_coefficient(left_normalized, right_normalized) * 100.0).round() as i32);
-    score = score.max((dice_coefficient(left_stripped, right_stripped) * 112.0).round() as i32);
-
-    score
-}
-
-fn normalize_title(value: &str) -> String {
-    value
-        .chars()
-        .filter(|character| character.is_alphanumeric())
-        .flat_map(char::to_lowercase)
-        .collect()
-}
-
 fn strip_variant(value: &str) -> String {
     value
         .chars()
@@ -1042,50 +1211,6 @@ fn broadcast_label_date_regex() -> &'static Regex {
     })
 }
 
-fn dice_coefficient(left: &str, right: &str) -> f32 {
-    if left.is_empty() || right.is_empty() {
-        return 0.0;
-    }
-    if left == right {
-        return 1.0;
-    }
-
-    let left_pairs = bigrams(left);
-    let right_pairs = bigrams(right);
-    if left_pairs.is_empty() || right_pairs.is_empty() {
-        return 0.0;
-    }
-
-    let mut overlap = 0usize;
-    let mut counts = HashMap::new();
-    for pair in &left_pairs {
-        *counts.entry(pair.clone()).or_insert(0usize) += 1;
-    }
-
-    for pair in &right_pairs {
-        if let Some(count) = counts.get_mut(pair) {
-            if *count > 0 {
-                *count -= 1;
-                overlap += 1;
-            }
-        }
-    }
-
-    (2 * overlap) as f32 / (left_pairs.len() + right_pairs.len()) as f32
-}
-
-fn bigrams(value: &str) -> Vec<String> {
-    let chars = value.chars().collect::<Vec<_>>();
-    if chars.len() < 2 {
-        return Vec::new();
-    }
-
-    chars
-        .windows(2)
-        .map(|window| window.iter().collect::<String>())
-        .collect()
-}
-
 fn hash_catalog_payload(title: &str, entries: &[CatalogEntrySeed]) -> Result<String, AppError> {
     let source = serde_json::to_string(&(
         title,
@@ -1152,3 +1277,92 @@ fn normalize_image_url(value: Option<&str>) -> Option<String> {
 
     Some(value.to_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{load_subject_episodes, store_subject_episodes};
+    use crate::{
+        bangumi::{BangumiClient, EpisodeRaw},
+        config::AppConfig,
+    };
+    use axum::{Json, Router, routing::get};
+    use serde_json::json;
+    use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+
+    async fn memory_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("sqlite pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("run migrations");
+        pool
+    }
+
+    fn client(base_url: String) -> BangumiClient {
+        let mut config = AppConfig::default().bangumi;
+        config.base_url = base_url;
+        config.min_request_interval_ms = 0;
+        BangumiClient::new(&config).expect("bangumi client")
+    }
+
+    fn episode(id: i64) -> EpisodeRaw {
+        serde_json::from_value(json!({ "id": id, "sort": id })).expect("episode")
+    }
+
+    #[tokio::test]
+    async fn fresh_episode_cache_is_served_without_bangumi() {
+        let pool = memory_pool().await;
+        store_subject_episodes(&pool, 7, &[episode(1), episode(2)], &super::now_string())
+            .await
+            .expect("store episodes");
+
+        let episodes = load_subject_episodes(&pool, &client("http://127.0.0.1:9".to_owned()), 7)
+            .await
+            .expect("cached episodes");
+        assert_eq!(
+            episodes
+                .iter()
+                .map(|episode| episode.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_episode_cache_is_refetched_and_stored() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind stub");
+        let address = listener.local_addr().expect("stub address");
+        let router = Router::new().route(
+            "/v0/episodes",
+            get(|| async {
+                Json(json!({ "data": [{ "id": 3, "sort": 1 }], "total": 1, "limit": 100 }))
+            }),
+        );
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.expect("serve stub");
+        });
+
+        let pool = memory_pool().await;
+        store_subject_episodes(&pool, 7, &[episode(1)], "2020-01-01T00:00:00Z")
+            .await
+            .expect("store stale episodes");
+
+        let bangumi = client(format!("http://{address}"));
+        let episodes = load_subject_episodes(&pool, &bangumi, 7)
+            .await
+            .expect("refetched episodes");
+        assert_eq!(episodes[0].id, 3);
+
+        let offline = client("http://127.0.0.1:9".to_owned());
+        let cached = load_subject_episodes(&pool, &offline, 7)
+            .await
+            .expect("stored episodes");
+        assert_eq!(cached[0].id, 3);
+    }
+}
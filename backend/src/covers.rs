@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use sqlx::SqlitePool;
+use tokio::fs;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{bangumi::BangumiClient, db, types::AppError};
+
+const MAX_COVER_BYTES: usize = 5 * 1024 * 1024;
+const COVER_EXTENSIONS: [(&str, &str); 4] = [
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/webp", "webp"),
+    ("image/gif", "gif"),
+];
+
+/// Returns the locally cached cover for a subject already in `bangumi_subject_cache`,
+/// downloading it once from the cached image URL. Unknown subjects are never fetched.
+pub async fn cache_subject_cover(
+    pool: &SqlitePool,
+    bangumi: &BangumiClient,
+    cache_root: &Path,
+    bangumi_subject_id: i64,
+) -> Result<PathBuf, AppError> {
+    let cover_root = cache_root.join("covers");
+    if let Some(cover_path) = find_cached_cover(&cover_root, bangumi_subject_id).await {
+        return Ok(cover_path);
+    }
+
+    let image_url = db::cached_bangumi_subject_image(pool, bangumi_subject_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("subject cover is not available"))?;
+    let image = bangumi.fetch_image(&image_url, MAX_COVER_BYTES).await?;
+    let extension = cover_extension(&image.content_type).ok_or_else(|| {
+        warn!(
+            subject_id = bangumi_subject_id,
+            content_type = %image.content_type,
+            "Bangumi cover has an unsupported content type"
+        );
+        AppError::upstream("Bangumi cover has an unsupported image type")
+    })?;
+
+    fs::create_dir_all(&cover_root).await.map_err(|error| {
+        warn!(
+            path = %cover_root.display(),
+            error = %error,
+            "Failed to create subject cover cache directory"
+        );
+        AppError::internal("failed to prepare subject cover cache")
+    })?;
+
+    let cover_path = cover_root.join(format!("{bangumi_subject_id}.{extension}"));
+    let staging_path = cover_root.join(format!(
+        "{bangumi_subject_id}.{}.part",
+        Uuid::new_v4().simple()
+    ));
+    let stored = match fs::write(&staging_path, &image.bytes).await {
+        Ok(()) => fs::rename(&staging_path, &cover_path).await,
+        Err(error) => Err(error),
+    };
+    if let Err(error) = stored {
+        let _ = fs::remove_file(&staging_path).await;
+        warn!(
+            subject_id = bangumi_subject_id,
+            path = %cover_path.display(),
+            error = %error,
+            "Failed to store subject cover image"
+        );
+        return Err(AppError::internal("failed to store subject cover image"));
+    }
+
+    Ok(cover_path)
+}
+
+async fn find_cached_cover(cover_root: &Path, bangumi_subject_id: i64) -> Option<PathBuf> {
+    for (_, extension) in COVER_EXTENSIONS {
+        let path = cover_root.join(format!("{bangumi_subject_id}.{extension}"));
+        if fs::metadata(&path)
+            .await
+            .is_ok_and(|metadata| metadata.is_file())
+        {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+fn cover_extension(content_type: &str) -> Option<&'static str> {
+    COVER_EXTENSIONS
+        .iter()
+        .find(|(mime, _)| *mime == content_type)
+        .map(|(_, extension)| *extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_COVER_BYTES, cache_subject_cover, cover_extension};
+    use crate::{bangumi::BangumiClient, config::AppConfig, types::AppError};
+    use axum::{Router, http::header, routing::get};
+    use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+    use std::path::PathBuf;
+
+    async fn memory_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("sqlite pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("run migrations");
+        pool
+    }
+
+    async fn image_host(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind image host");
+        let address = listener.local_addr().expect("image host address");
+        tokio::spawn(async move {
+            axum::serve(listener, router)
+                .await
+                .expect("serve image host");
+        });
+        format!("http://{address}")
+    }
+
+    async fn cache_subject(pool: &SqlitePool, subject_id: i64, image_url: &str) {
+        sqlx::query(
+            "INSERT INTO bangumi_subject_cache (
+                bangumi_subject_id, title, title_cn, summary, image_portrait, release_status,
+                metadata_refreshed_at, status_refreshed_at
+             ) VALUES (?1, 'Sample', '', '', ?2, 'airing',
+                '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        )
+        .bind(subject_id)
+        .bind(image_url)
+        .execute(pool)
+        .await
+        .expect("cache subject");
+    }
+
+    fn cache_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("anicargo-cover-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    fn bangumi() -> BangumiClient {
+        BangumiClient::new(&AppConfig::default().bangumi).expect("bangumi client")
+    }
+
+    #[test]
+    fn extension_follows_content_type() {
+        assert_eq!(cover_extension("image/png"), Some("png"));
+        assert_eq!(cover_extension("image/jpeg"), Some("jpg"));
+        assert_eq!(cover_extension("text/html"), None);
+    }
+
+    #[tokio::test]
+    async fn uncached_subjects_are_not_fetched() {
+        let pool = memory_pool().await;
+        let root = cache_root("uncached");
+
+        let error = cache_subject_cover(&pool, &bangumi(), &root, 404)
+            .await
+            .expect_err("unknown subject");
+        assert!(matches!(error, AppError::NotFound(_)));
+        assert!(!root.join("covers").exists());
+    }
+
+    #[tokio::test]
+    async fn stores_cover_with_extension_from_content_type() {
+        let host = image_host(Router::new().route(
+            "/cover",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "image/png")],
+                    vec![0x89, b'P', b'N', b'G'],
+                )
+            }),
+        ))
+        .await;
+        let pool = memory_pool().await;
+        cache_subject(&pool, 7, &format!("{host}/cover")).await;
+        let root = cache_root("png");
+
+        let path = cache_subject_cover(&pool, &bangumi(), &root, 7)
+            .await
+            .expect("cached cover");
+        assert_eq!(path, root.join("covers").join("7.png"));
+        assert_eq!(std::fs::read(&path).expect("cover bytes"), b"\x89PNG");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_covers() {
+        let host = image_host(Router::new().route(
+            "/cover",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "image/jpeg")],
+                    vec![0u8; MAX_COVER_BYTES + 1],
+                )
+            }),
+        ))
+        .await;
+        let pool = memory_pool().await;
+        cache_subject(&pool, 8, &format!("{host}/cover")).await;
+        let root = cache_root("oversized");
+
+        assert!(
+            cache_subject_cover(&pool, &bangumi(), &root, 8)
+                .await
+                .is_err()
+        );
+        assert!(!root.join("covers").join("8.jpg").exists());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs, path::Path};
+use std::{collections::BTreeSet, fs, path::Path};
 
 use anyhow::Context;
 use chrono::{Duration, Utc};
@@ -6,9 +6,13 @@ use sqlx::{
     FromRow, QueryBuilder, SqlitePool,
     sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
 };
+use tracing::info;
 
 use crate::{
-    auth::{AdminIdentity, ViewerIdentity, generate_token, hash_password, verify_password},
+    auth::{
+        AdminIdentity, ViewerIdentity, generate_token, hash_password, normalize_username,
+        verify_password,
+    },
     config::{AppConfig, AuthConfig},
     types::{
         AdminCountsDto, AppError, DownloadExecutionDto, DownloadExecutionEventDto, DownloadJobDto,
@@ -140,6 +144,7 @@ struct DownloadExecutionRow {
     replaced_at: Option<String>,
     failed_at: Option<String>,
     last_indexed_at: Option<String>,
+    rescan_held_at: Option<String>,
 }
 
 #[derive(Debug, FromRow)]
@@ -392,11 +397,16 @@ pub async fn connect_and_migrate(config: &AppConfig) -> anyhow::Result<SqlitePoo
         .foreign_keys(true);
 
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
+        .max_connections(config.storage.database_max_connections)
+        .acquire_timeout(std::time::Duration::from_secs(
+            config.storage.database_acquire_timeout_secs,
+        ))
         .connect_with(options)
         .await
         .context("failed to connect to sqlite")?;
 
+    ensure_no_username_collisions(&pool).await?;
+
     sqlx::migrate!("./migrations")
         .run(&pool)
         .await
@@ -405,20 +415,63 @@ pub async fn connect_and_migrate(config: &AppConfig) -> anyhow::Result<SqlitePoo
     Ok(pool)
 }
 
+/// Refuses to start while accounts differ only by username case or surrounding
+/// whitespace, since the unique index on the normalized name cannot be built then.
+async fn ensure_no_username_collisions(pool: &SqlitePool) -> anyhow::Result<()> {
+    let has_users = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'",
+    )
+    .fetch_one(pool)
+    .await
+    .context("failed to check for the users table")?;
+    if has_users == 0 {
+        return Ok(());
+    }
+
+    let collisions = sqlx::query_scalar::<_, String>(
+        "SELECT group_concat(quote(username), ', ')
+         FROM users
+         GROUP BY lower(trim(username))
+         HAVING COUNT(*) > 1",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to check for username collisions")?;
+
+    if !collisions.is_empty() {
+        anyhow::bail!(
+            "accounts differ only by username case or whitespace: [{}]; rename or delete \
+             all but one account in each group in the users table, then restart",
+            collisions.join("], [")
+        );
+    }
+
+    Ok(())
+}
+
 pub async fn ensure_bootstrap_admin(pool: &SqlitePool, auth: &AuthConfig) -> Result<(), AppError> {
-    let existing_default_user = sqlx::query_scalar::<_, i64>("SELECT id FROM users WHERE username = ?1")
-        .bind(&auth.default_admin_username)
-        .fetch_optional(pool)
-        .await
-        .map_err(|_| AppError::internal("failed to query bootstrap admin user"))?;
+    let admin_username = normalize_username(&auth.default_admin_username);
+    let existing_default_user = sqlx::query_as::<_, (i64, Option<String>)>(
+        "SELECT id, bootstrap_password_hash FROM users WHERE username = ?1",
+    )
+    .bind(&admin_username)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to query bootstrap admin user"))?;
 
-    if let Some(user_id) = existing_default_user {
+    if let Some((user_id, bootstrap_password_hash)) = existing_default_user {
         sqlx::query("UPDATE users SET is_admin = 1 WHERE id = ?1")
             .bind(user_id)
             .execute(pool)
             .await
             .map_err(|_| AppError::internal("failed to promote bootstrap admin user"))?;
-        return Ok(());
+        return sync_bootstrap_admin_password(
+            pool,
+            user_id,
+            bootstrap_password_hash.as_deref(),
+            auth,
+        )
+        .await;
     }
 
     let existing = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users WHERE is_admin = 1")
@@ -428,7 +481,7 @@ pub async fn ensure_bootstrap_admin(pool: &SqlitePool, auth: &AuthConfig) -> Res
 
     if existing > 0 {
         sqlx::query("UPDATE users SET is_admin = 1 WHERE username = ?1")
-            .bind(&auth.default_admin_username)
+            .bind(&admin_username)
             .execute(pool)
             .await
             .map_err(|_| AppError::internal("failed to refresh bootstrap admin flag"))?;
@@ -438,9 +491,10 @@ pub async fn ensure_bootstrap_admin(pool: &SqlitePool, auth: &AuthConfig) -> Res
     let password_hash = hash_password(&auth.default_admin_password)?;
 
     sqlx::query(
-        "INSERT INTO users (username, password_hash, is_admin, created_at) VALUES (?1, ?2, 1, ?3)",
+        "INSERT INTO users (username, password_hash, bootstrap_password_hash, is_admin, created_at)
+         VALUES (?1, ?2, ?2, 1, ?3)",
     )
-    .bind(&auth.default_admin_username)
+    .bind(&admin_username)
     .bind(password_hash)
     .bind(now_string())
     .execute(pool)
@@ -450,6 +504,42 @@ pub async fn ensure_bootstrap_admin(pool: &SqlitePool, auth: &AuthConfig) -> Res
     Ok(())
 }
 
+/// Applies the configured admin password only when it changed since it was last
+/// applied, so a password set later through the app survives restarts.
+async fn sync_bootstrap_admin_password(
+    pool: &SqlitePool,
+    user_id: i64,
+    bootstrap_password_hash: Option<&str>,
+    auth: &AuthConfig,
+) -> Result<(), AppError> {
+    let config_changed = bootstrap_password_hash
+        .is_some_and(|applied| !verify_password(applied, &auth.default_admin_password));
+    let apply = auth.force_admin_reset || config_changed;
+    if !apply && bootstrap_password_hash.is_some() {
+        return Ok(());
+    }
+
+    let configured_hash = hash_password(&auth.default_admin_password)?;
+    let statement = if apply {
+        "UPDATE users SET password_hash = ?1, bootstrap_password_hash = ?1 WHERE id = ?2"
+    } else {
+        "UPDATE users SET bootstrap_password_hash = ?1 WHERE id = ?2"
+    };
+
+    sqlx::query(statement)
+        .bind(configured_hash)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(|_| AppError::internal("failed to update bootstrap admin password"))?;
+
+    if apply {
+        info!(user_id, "Applied configured password to bootstrap admin");
+    }
+
+    Ok(())
+}
+
 pub async fn touch_device(pool: &SqlitePool, device_id: &str) -> Result<(), AppError> {
     let now = now_string();
 
@@ -467,19 +557,59 @@ pub async fn touch_device(pool: &SqlitePool, device_id: &str) -> Result<(), AppE
     Ok(())
 }
 
+const WORKER_HEARTBEAT_RETENTION_HOURS: i64 = 24;
+
+pub async fn record_worker_heartbeat(pool: &SqlitePool, worker_id: &str) -> Result<(), AppError> {
+    let now = Utc::now();
+
+    sqlx::query(
+        "INSERT INTO worker_heartbeats (worker_id, last_seen_at)
+         VALUES (?1, ?2)
+         ON CONFLICT(worker_id) DO UPDATE SET last_seen_at = excluded.last_seen_at",
+    )
+    .bind(worker_id)
+    .bind(now.to_rfc3339())
+    .execute(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to store worker heartbeat"))?;
+
+    sqlx::query("DELETE FROM worker_heartbeats WHERE last_seen_at < ?1")
+        .bind((now - Duration::hours(WORKER_HEARTBEAT_RETENTION_HOURS)).to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(|_| AppError::internal("failed to prune worker heartbeats"))?;
+
+    Ok(())
+}
+
+pub async fn worker_health(pool: &SqlitePool, stale_secs: u64) -> Result<bool, AppError> {
+    let cutoff = Utc::now() - Duration::seconds(stale_secs.min(i64::MAX as u64) as i64);
+
+    let (recent,) = sqlx::query_as::<_, (i64,)>(
+        "SELECT COUNT(*) FROM worker_heartbeats WHERE last_seen_at >= ?1",
+    )
+    .bind(cutoff.to_rfc3339())
+    .fetch_one(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to read worker heartbeats"))?;
+
+    Ok(recent > 0)
+}
+
 pub async fn register_user(
     pool: &SqlitePool,
     username: &str,
     password: &str,
     auth: &AuthConfig,
 ) -> Result<(ViewerIdentity, String), AppError> {
+    let username = normalize_username(username);
     let password_hash = hash_password(password)?;
     let created_at = now_string();
 
     let result = sqlx::query(
         "INSERT INTO users (username, password_hash, is_admin, created_at) VALUES (?1, ?2, 0, ?3)",
     )
-            .bind(username)
+            .bind(&username)
             .bind(password_hash)
             .bind(created_at)
             .execute(pool)
@@ -487,7 +617,10 @@ pub async fn register_user(
 
     let user_id = match result {
         Ok(result) => result.last_insert_rowid(),
-        Err(_) => return Err(AppError::bad_request("username is already in use")),
+        Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+            return Err(AppError::conflict("username is already in use"));
+        }
+        Err(_) => return Err(AppError::internal("failed to create user")),
     };
 
     let token = create_user_session(pool, user_id, auth.user_session_days).await?;
@@ -495,7 +628,7 @@ pub async fn register_user(
     Ok((
         ViewerIdentity::User {
             id: user_id,
-            username: username.to_owned(),
+            username,
             is_admin: false,
         },
         token,
@@ -508,9 +641,17 @@ pub async fn login_user(
     password: &str,
     auth: &AuthConfig,
 ) -> Result<(ViewerIdentity, String), AppError> {
-    let Some(user) =
-        sqlx::query_as::<_, UserRow>("SELECT id, username, password_hash, is_admin FROM users WHERE username = ?1")
-    .bind(username)
+    // SQLite's lower() only folds ASCII, so a legacy name with non-ASCII capitals
+    // is still found by its exact trimmed spelling.
+    let Some(user) = sqlx::query_as::<_, UserRow>(
+        "SELECT id, username, password_hash, is_admin
+         FROM users
+         WHERE username IN (?1, ?2)
+         ORDER BY username = ?2 DESC
+         LIMIT 1",
+    )
+    .bind(normalize_username(username))
+    .bind(username.trim())
     .fetch_optional(pool)
     .await
     .map_err(|_| AppError::internal("failed to query user"))?
@@ -1355,6 +1496,27 @@ pub async fn cached_bangumi_subject_summary(
     }))
 }
 
+pub async fn cached_bangumi_subject_image(
+    pool: &SqlitePool,
+    bangumi_subject_id: i64,
+) -> Result<Option<String>, AppError> {
+    let image = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT COALESCE(image_portrait, image_banner)
+         FROM bangumi_subject_cache
+         WHERE bangumi_subject_id = ?1
+         LIMIT 1",
+    )
+    .bind(bangumi_subject_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to read cached Bangumi subject image"))?;
+
+    Ok(image
+        .flatten()
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty()))
+}
+
 pub async fn subject_download_status(
     pool: &SqlitePool,
     bangumi_subject_id: i64,
@@ -1479,9 +1641,30 @@ pub async fn subject_download_status(
 
 pub async fn list_download_jobs(
     pool: &SqlitePool,
+    lifecycle: Option<&str>,
+    trigger_kind: Option<&str>,
     limit: usize,
-) -> Result<Vec<DownloadJobDto>, AppError> {
-    let limit = limit.clamp(1, 100) as i64;
+    offset: usize,
+) -> Result<(usize, Vec<DownloadJobDto>), AppError> {
+    let limit = limit as i64;
+    let offset = offset as i64;
+    let lifecycle = lifecycle.map(str::trim).filter(|value| !value.is_empty());
+    let trigger_kind = trigger_kind
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+
+    let total = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*)
+         FROM download_jobs
+         WHERE (?1 IS NULL OR lifecycle = ?1)
+           AND (?2 IS NULL OR trigger_kind = ?2)",
+    )
+    .bind(lifecycle)
+    .bind(trigger_kind)
+    .fetch_one(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to count download jobs"))?;
+
     let rows = sqlx::query_as::<_, DownloadJobRow>(
         "SELECT
             id,
@@ -1503,15 +1686,23 @@ pub async fn list_download_jobs(
             created_at,
             updated_at
          FROM download_jobs
-         ORDER BY created_at DESC
-         LIMIT ?1",
+         WHERE (?1 IS NULL OR lifecycle = ?1)
+           AND (?2 IS NULL OR trigger_kind = ?2)
+         ORDER BY created_at DESC, id DESC
+         LIMIT ?3 OFFSET ?4",
     )
+    .bind(lifecycle)
+    .bind(trigger_kind)
     .bind(limit)
+    .bind(offset)
     .fetch_all(pool)
     .await
     .map_err(|_| AppError::internal("failed to list download jobs"))?;
 
-    Ok(rows.into_iter().map(map_download_job).collect())
+    Ok((
+        total.max(0) as usize,
+        rows.into_iter().map(map_download_job).collect(),
+    ))
 }
 
 pub async fn update_download_job_lifecycle(
@@ -2046,6 +2237,7 @@ pub async fn mark_download_execution_indexed(
     sqlx::query(
         "UPDATE download_executions
          SET last_indexed_at = ?2,
+             rescan_held_at = NULL,
              updated_at = ?2
          WHERE id = ?1",
     )
@@ -2058,25 +2250,118 @@ pub async fn mark_download_execution_indexed(
     Ok(())
 }
 
+pub async fn mark_download_execution_rescan_held(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<(), AppError> {
+    let now = now_string();
+
+    sqlx::query(
+        "UPDATE download_executions
+         SET rescan_held_at = ?2,
+             updated_at = ?2
+         WHERE id = ?1",
+    )
+    .bind(execution_id)
+    .bind(&now)
+    .execute(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to mark download execution rescan as held"))?;
+
+    Ok(())
+}
+
+/// Records that the completion webhook went out for an execution. Returns `false` when it was
+/// already recorded, so each execution is announced at most once.
+pub async fn claim_execution_completion_notification(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        "UPDATE download_executions
+         SET completion_notified_at = ?2
+         WHERE id = ?1
+           AND completion_notified_at IS NULL",
+    )
+    .bind(execution_id)
+    .bind(now_string())
+    .execute(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to record download completion notification"))?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn count_media_inventory_for_execution(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*)
+         FROM media_inventory
+         WHERE download_execution_id = ?1",
+    )
+    .bind(execution_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to count media inventory rows"))
+}
+
+// 16 binds per row keeps each statement under SQLite's 999 parameter limit.
+const MEDIA_INVENTORY_INSERT_BATCH: usize = 60;
+
+/// Writes the rescanned rows for an execution and returns the relative paths of
+/// indexed rows the rescan did not find. Those rows are deleted only when
+/// `delete_stale` is set; otherwise they are left in place.
 pub async fn replace_media_inventory_for_execution(
     pool: &SqlitePool,
     execution_id: i64,
     items: &[NewMediaInventoryItem],
-) -> Result<(), AppError> {
+    delete_stale: bool,
+) -> Result<Vec<String>, AppError> {
     let mut tx = pool
         .begin()
         .await
         .map_err(|_| AppError::internal("failed to start media inventory transaction"))?;
 
-    sqlx::query("DELETE FROM media_inventory WHERE download_execution_id = ?1")
-        .bind(execution_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(|_| AppError::internal("failed to clear media inventory rows"))?;
+    let existing = sqlx::query_as::<_, (i64, String, String)>(
+        "SELECT bangumi_subject_id, slot_key, relative_path
+         FROM media_inventory
+         WHERE download_execution_id = ?1",
+    )
+    .bind(execution_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| AppError::internal("failed to load media inventory rows"))?;
+    let found = items
+        .iter()
+        .map(|item| {
+            (
+                item.bangumi_subject_id,
+                item.slot_key.as_str(),
+                item.relative_path.as_str(),
+            )
+        })
+        .collect::<BTreeSet<_>>();
+    let stale = existing
+        .iter()
+        .filter(|(subject_id, slot_key, relative_path)| {
+            !found.contains(&(*subject_id, slot_key.as_str(), relative_path.as_str()))
+        })
+        .map(|(_, _, relative_path)| relative_path.clone())
+        .collect::<BTreeSet<_>>();
 
-    for item in items {
-        let now = now_string();
-        sqlx::query(
+    if delete_stale {
+        sqlx::query("DELETE FROM media_inventory WHERE download_execution_id = ?1")
+            .bind(execution_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| AppError::internal("failed to clear media inventory rows"))?;
+    }
+
+    let now = now_string();
+    for chunk in items.chunks(MEDIA_INVENTORY_INSERT_BATCH) {
+        let mut query = QueryBuilder::new(
             "INSERT INTO media_inventory (
                 bangumi_subject_id,
                 download_job_id,
@@ -2094,33 +2379,54 @@ pub async fn replace_media_inventory_for_execution(
                 status,
                 created_at,
                 updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?15)",
-        )
-        .bind(item.bangumi_subject_id)
-        .bind(item.download_job_id)
-        .bind(item.download_execution_id)
-        .bind(item.resource_candidate_id)
-        .bind(&item.slot_key)
-        .bind(&item.relative_path)
-        .bind(&item.absolute_path)
-        .bind(&item.file_name)
-        .bind(&item.file_ext)
-        .bind(item.size_bytes)
-        .bind(item.episode_index)
-        .bind(item.episode_end_index)
-        .bind(bool_to_int(item.is_collection))
-        .bind(&item.status)
-        .bind(&now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|_| AppError::internal("failed to insert media inventory row"))?;
+            ) ",
+        );
+        query.push_values(chunk, |mut row, item| {
+            row.push_bind(item.bangumi_subject_id)
+                .push_bind(item.download_job_id)
+                .push_bind(item.download_execution_id)
+                .push_bind(item.resource_candidate_id)
+                .push_bind(&item.slot_key)
+                .push_bind(&item.relative_path)
+                .push_bind(&item.absolute_path)
+                .push_bind(&item.file_name)
+                .push_bind(&item.file_ext)
+                .push_bind(item.size_bytes)
+                .push_bind(item.episode_index)
+                .push_bind(item.episode_end_index)
+                .push_bind(bool_to_int(item.is_collection))
+                .push_bind(&item.status)
+                .push_bind(&now)
+                .push_bind(&now);
+        });
+        query.push(
+            " ON CONFLICT(download_execution_id, bangumi_subject_id, slot_key, relative_path)
+             DO UPDATE SET
+                download_job_id = excluded.download_job_id,
+                resource_candidate_id = excluded.resource_candidate_id,
+                absolute_path = excluded.absolute_path,
+                file_name = excluded.file_name,
+                file_ext = excluded.file_ext,
+                size_bytes = excluded.size_bytes,
+                episode_index = excluded.episode_index,
+                episode_end_index = excluded.episode_end_index,
+                is_collection = excluded.is_collection,
+                status = excluded.status,
+                updated_at = excluded.updated_at",
+        );
+
+        query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| AppError::internal("failed to insert media inventory rows"))?;
     }
 
     tx.commit()
         .await
         .map_err(|_| AppError::internal("failed to commit media inventory transaction"))?;
 
-    Ok(())
+    Ok(stale.into_iter().collect())
 }
 
 pub async fn delete_media_inventory_for_execution(
@@ -2221,6 +2527,54 @@ pub async fn mark_download_execution_replaced(
     Ok(())
 }
 
+pub async fn download_execution_by_id(
+    pool: &SqlitePool,
+    execution_id: i64,
+) -> Result<Option<DownloadExecutionDto>, AppError> {
+    let row = sqlx::query_as::<_, DownloadExecutionRow>(
+        "SELECT
+            id,
+            download_job_id,
+            resource_candidate_id,
+            bangumi_subject_id,
+            slot_key,
+            episode_index,
+            episode_end_index,
+            is_collection,
+            engine_name,
+            engine_execution_ref,
+            execution_role,
+            state,
+            target_path,
+            source_title,
+            source_magnet,
+            source_size_bytes,
+            source_fansub_name,
+            downloaded_bytes,
+            uploaded_bytes,
+            download_rate_bytes,
+            upload_rate_bytes,
+            peer_count,
+            notes,
+            created_at,
+            updated_at,
+            started_at,
+            completed_at,
+            replaced_at,
+            failed_at,
+            last_indexed_at,
+            rescan_held_at
+         FROM download_executions
+         WHERE id = ?1",
+    )
+    .bind(execution_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| AppError::internal("failed to load download execution"))?;
+
+    Ok(row.map(map_download_execution))
+}
+
 pub async fn list_download_executions(
     pool: &SqlitePool,
     download_job_id: i64,
@@ -3119,6 +3473,7 @@ fn map_download_execution(row: DownloadExecutionRow) -> DownloadExecutionDto {
         replaced_at: row.replaced_at,
         failed_at: row.failed_at,
         last_indexed_at: row.last_indexed_at,
+        rescan_held_at: row.rescan_held_at,
     }
 }
 
@@ -3161,3 +3516,168 @@ fn map_resource_library_item(row: ResourceLibraryRow) -> ResourceLibraryItemDto
         updated_at: row.updated_at,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        MEDIA_INVENTORY_INSERT_BATCH, NewMediaInventoryItem,
+        claim_execution_completion_notification, list_download_jobs,
+        replace_media_inventory_for_execution,
+    };
+    use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+
+    async fn memory_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("sqlite pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("run migrations");
+        pool
+    }
+
+    fn inventory_item(relative_path: &str) -> NewMediaInventoryItem {
+        NewMediaInventoryItem {
+            bangumi_subject_id: 1,
+            download_job_id: 1,
+            download_execution_id: 7,
+            resource_candidate_id: 1,
+            slot_key: "batch:1-2".to_owned(),
+            relative_path: relative_path.to_owned(),
+            absolute_path: format!("/media/{relative_path}"),
+            file_name: relative_path.to_owned(),
+            file_ext: "mkv".to_owned(),
+            size_bytes: 1,
+            episode_index: None,
+            episode_end_index: None,
+            is_collection: true,
+            status: "ready".to_owned(),
+        }
+    }
+
+    async fn indexed_paths(pool: &SqlitePool) -> Vec<String> {
+        sqlx::query_scalar(
+            "SELECT relative_path FROM media_inventory
+             WHERE download_execution_id = 7
+             ORDER BY relative_path",
+        )
+        .fetch_all(pool)
+        .await
+        .expect("indexed paths")
+    }
+
+    async fn insert_download_job(pool: &SqlitePool, trigger_kind: &str, lifecycle: &str) {
+        sqlx::query(
+            "INSERT INTO download_jobs (
+                bangumi_subject_id, trigger_kind, requested_by, release_status, season_mode,
+                lifecycle, subscription_count, threshold_snapshot, engine_name,
+                created_at, updated_at
+             ) VALUES (1, ?1, 'system', 'airing', 'single', ?2, 1, 1, 'planning',
+                '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        )
+        .bind(trigger_kind)
+        .bind(lifecycle)
+        .execute(pool)
+        .await
+        .expect("insert download job");
+    }
+
+    #[tokio::test]
+    async fn download_job_list_filters_by_lifecycle_and_trigger_kind() {
+        let pool = memory_pool().await;
+        insert_download_job(&pool, "subscription", "downloading").await;
+        insert_download_job(&pool, "subscription", "failed").await;
+        insert_download_job(&pool, "admin_force", "downloading").await;
+
+        let (total, items) = list_download_jobs(&pool, None, None, 50, 0)
+            .await
+            .expect("all jobs");
+        assert_eq!((total, items.len()), (3, 3));
+
+        let (total, items) = list_download_jobs(&pool, Some("downloading"), None, 50, 0)
+            .await
+            .expect("downloading jobs");
+        assert_eq!(total, 2);
+        assert!(items.iter().all(|job| job.lifecycle == "downloading"));
+
+        let (total, items) =
+            list_download_jobs(&pool, Some("downloading"), Some("admin_force"), 50, 0)
+                .await
+                .expect("forced downloading jobs");
+        assert_eq!(total, 1);
+        assert_eq!(items[0].trigger_kind, "admin_force");
+
+        let (total, items) = list_download_jobs(&pool, Some(" "), Some("subscription"), 1, 1)
+            .await
+            .expect("second subscription page");
+        assert_eq!((total, items.len()), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn completion_notification_is_claimed_once() {
+        let pool = memory_pool().await;
+        sqlx::query(
+            "INSERT INTO download_executions (
+                id, download_job_id, resource_candidate_id, bangumi_subject_id, engine_name,
+                execution_role, state, target_path, source_title, source_magnet,
+                source_size_bytes, created_at, updated_at
+             ) VALUES (7, 1, 1, 1, 'planning', 'primary', 'completed', '/media', 'Sample',
+                'magnet:?xt=urn:btih:sample', 1, '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        )
+        .execute(&pool)
+        .await
+        .expect("insert execution");
+
+        assert!(
+            claim_execution_completion_notification(&pool, 7)
+                .await
+                .expect("first claim")
+        );
+        assert!(
+            !claim_execution_completion_notification(&pool, 7)
+                .await
+                .expect("second claim")
+        );
+    }
+
+    #[tokio::test]
+    async fn inventory_rows_beyond_one_insert_batch_all_land() {
+        let pool = memory_pool().await;
+        let items = (0..MEDIA_INVENTORY_INSERT_BATCH * 8 + 20)
+            .map(|index| inventory_item(&format!("{index:03}.mkv")))
+            .collect::<Vec<_>>();
+
+        replace_media_inventory_for_execution(&pool, 7, &items, true)
+            .await
+            .expect("index every file");
+        let paths = indexed_paths(&pool).await;
+        assert_eq!(paths.len(), items.len());
+        assert_eq!(paths.first().map(String::as_str), Some("000.mkv"));
+        assert_eq!(paths.last().map(String::as_str), Some("499.mkv"));
+    }
+
+    #[tokio::test]
+    async fn rescan_keeps_or_removes_stale_rows_as_configured() {
+        let pool = memory_pool().await;
+        let both = [inventory_item("01.mkv"), inventory_item("02.mkv")];
+        let first = [inventory_item("01.mkv")];
+        replace_media_inventory_for_execution(&pool, 7, &both, true)
+            .await
+            .expect("index both files");
+
+        let stale = replace_media_inventory_for_execution(&pool, 7, &first, false)
+            .await
+            .expect("rescan without deletion");
+        assert_eq!(stale, vec!["02.mkv".to_owned()]);
+        assert_eq!(indexed_paths(&pool).await, vec!["01.mkv", "02.mkv"]);
+
+        let stale = replace_media_inventory_for_execution(&pool, 7, &first, true)
+            .await
+            .expect("rescan with deletion");
+        assert_eq!(stale, vec!["02.mkv".to_owned()]);
+        assert_eq!(indexed_paths(&pool).await, vec!["01.mkv"]);
+    }
+}
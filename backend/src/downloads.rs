@@ -24,13 +24,17 @@ use librqbit::{
 };
 use sqlx::SqlitePool;
 use tokio::time::{Duration as TokioDuration, timeout};
-use tracing::{info, warn};
+use tracing::{Span, debug, field, info, instrument, warn};
 use uuid::Uuid;
 
+use anicargo_metadata_parser::Parser;
+
 use crate::{
     bangumi::BangumiClient,
+    config::DEFAULT_MIN_RESCAN_RATIO,
     db,
     media::{ParsedReleaseSlot, scan_video_files},
+    notifications::WebhookNotifier,
     subject_parts,
     types::{
         AppError, DownloadDecisionDto, DownloadExecutionDecisionDto, DownloadExecutionDto,
@@ -40,6 +44,17 @@ use crate::{
 
 const ENGINE_SYNC_TIMEOUT_SECS: u64 = 8;
 
+/// How a rescan treats indexed rows whose files it did not find.
+#[derive(Debug, Clone, Copy)]
+enum RescanGuard {
+    /// Keep the existing rows when the rescan falls below this share of them.
+    MinRatio(f64),
+    /// Never remove rows; only log the ones that would have been removed.
+    KeepStale,
+    /// Accept whatever the rescan found.
+    Off,
+}
+
 #[derive(Debug, Clone)]
 pub struct DownloadDemandInput {
     pub bangumi_subject_id: i64,
@@ -683,7 +698,11 @@ impl DownloadEngine for RqbitDownloadEngine {
 pub struct DownloadCoordinator {
     engine: Arc<dyn DownloadEngine>,
     bangumi: Option<BangumiClient>,
+    parser: Arc<Parser>,
     runtime_settings: Arc<RwLock<DownloadRuntimeSettings>>,
+    notifier: Option<WebhookNotifier>,
+    min_rescan_ratio: f64,
+    delete_stale_media: bool,
 }
 
 impl DownloadCoordinator {
@@ -691,14 +710,42 @@ impl DownloadCoordinator {
         engine: Arc<dyn DownloadEngine>,
         runtime_settings: DownloadRuntimeSettings,
         bangumi: Option<BangumiClient>,
+        parser: Arc<Parser>,
     ) -> Self {
         Self {
             engine,
             bangumi,
+            parser,
             runtime_settings: Arc::new(RwLock::new(runtime_settings)),
+            notifier: None,
+            min_rescan_ratio: DEFAULT_MIN_RESCAN_RATIO,
+            delete_stale_media: true,
         }
     }
 
+    pub fn with_min_rescan_ratio(mut self, min_rescan_ratio: f64) -> Self {
+        self.min_rescan_ratio = min_rescan_ratio;
+        self
+    }
+
+    pub fn with_delete_stale_media(mut self, delete_stale_media: bool) -> Self {
+        self.delete_stale_media = delete_stale_media;
+        self
+    }
+
+    fn rescan_guard(&self) -> RescanGuard {
+        if self.delete_stale_media {
+            RescanGuard::MinRatio(self.min_rescan_ratio)
+        } else {
+            RescanGuard::KeepStale
+        }
+    }
+
+    pub fn with_notifier(mut self, notifier: Option<WebhookNotifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
     pub fn engine_name(&self) -> &'static str {
         self.engine.name()
     }
@@ -889,9 +936,12 @@ impl DownloadCoordinator {
     pub async fn list_jobs(
         &self,
         pool: &SqlitePool,
+        lifecycle: Option<&str>,
+        trigger_kind: Option<&str>,
         limit: usize,
-    ) -> Result<Vec<DownloadJobDto>, AppError> {
-        db::list_download_jobs(pool, limit).await
+        offset: usize,
+    ) -> Result<(usize, Vec<DownloadJobDto>), AppError> {
+        db::list_download_jobs(pool, lifecycle, trigger_kind, limit, offset).await
     }
 
     pub async fn materialize_selected_candidate(
@@ -1221,6 +1271,36 @@ impl DownloadCoordinator {
         db::list_download_executions(pool, job_id).await
     }
 
+    /// Rescans one execution's files and replaces its inventory even when the rescan
+    /// finds far fewer files than are indexed, e.g. after files were deleted on purpose.
+    pub async fn reindex_execution(
+        &self,
+        pool: &SqlitePool,
+        execution_id: i64,
+    ) -> Result<usize, AppError> {
+        let execution = db::download_execution_by_id(pool, execution_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("download execution not found"))?;
+        if !matches!(
+            execution.state.as_str(),
+            "downloading" | "seeding" | "completed"
+        ) {
+            return Err(AppError::bad_request(
+                "download execution has no files to index yet",
+            ));
+        }
+
+        sync_execution_media_inventory(
+            pool,
+            self.bangumi.as_ref(),
+            &self.parser,
+            &execution,
+            &execution.state,
+            RescanGuard::Off,
+        )
+        .await
+    }
+
     pub async fn sync_active_executions(
         &self,
         pool: &SqlitePool,
@@ -1263,22 +1343,43 @@ impl DownloadCoordinator {
                     .await?;
 
                     if should_refresh_media_index(&execution, &snapshot.state) {
-                        if let Err(error) = sync_execution_media_inventory(
+                        match sync_execution_media_inventory(
                             pool,
                             self.bangumi.as_ref(),
+                            &self.parser,
                             &execution,
                             &snapshot.state,
+                            self.rescan_guard(),
                         )
                         .await
                         {
-                            warn!(
+                            Ok(media_count) => {
+                                // Files can be indexed on a later pass than the completion
+                                // transition, so announce the first non-empty index instead.
+                                if let Some(notifier) = self.notifier.as_ref()
+                                    && is_completed_state(&snapshot.state)
+                                    && media_count > 0
+                                    && db::claim_execution_completion_notification(
+                                        pool,
+                                        execution.id,
+                                    )
+                                    .await?
+                                {
+                                    notifier.notify_execution_completed(
+                                        &execution,
+                                        &snapshot.state,
+                                        media_count,
+                                    );
+                                }
+                            }
+                            Err(error) => warn!(
                                 execution_id = execution.id,
                                 job_id = execution.download_job_id,
                                 subject_id = execution.bangumi_subject_id,
                                 state = %snapshot.state,
                                 error = %error,
                                 "Failed to refresh media inventory for synced execution"
-                            );
+                            ),
                         }
                     }
 
@@ -1426,12 +1527,24 @@ fn build_engine_activate_request(
     }
 }
 
+#[instrument(
+    skip_all,
+    fields(
+        execution_id = execution.id,
+        subject_id = execution.bangumi_subject_id,
+        state = %state,
+        existing = field::Empty,
+        scanned = field::Empty,
+    )
+)]
 async fn sync_execution_media_inventory(
     pool: &SqlitePool,
     bangumi: Option<&BangumiClient>,
+    parser: &Parser,
     execution: &DownloadExecutionDto,
     state: &str,
-) -> Result<(), AppError> {
+    guard: RescanGuard,
+) -> Result<usize, AppError> {
     let fallback_slot = ParsedReleaseSlot {
         slot_key: execution.slot_key.clone(),
         episode_index: execution.episode_index,
@@ -1443,8 +1556,8 @@ async fn sync_execution_media_inventory(
     } else {
         "partial"
     };
-    let files =
-        scan_video_files(Path::new(&execution.target_path), &fallback_slot).map_err(|error| {
+    let files = scan_video_files(parser, Path::new(&execution.target_path), &fallback_slot)
+        .map_err(|error| {
             warn!(
                 execution_id = execution.id,
                 path = %execution.target_path,
@@ -1457,6 +1570,7 @@ async fn sync_execution_media_inventory(
         match bangumi {
             Some(bangumi) => match subject_parts::resolve_subject_part_group(
                 bangumi,
+                parser,
                 execution.bangumi_subject_id,
             )
             .await
@@ -1484,8 +1598,76 @@ async fn sync_execution_media_inventory(
         })
         .collect::<Vec<_>>();
 
-    db::replace_media_inventory_for_execution(pool, execution.id, &items).await?;
+    let existing = db::count_media_inventory_for_execution(pool, execution.id).await?;
+    Span::current()
+        .record("existing", existing)
+        .record("scanned", items.len());
+    if let RescanGuard::MinRatio(min_rescan_ratio) = guard {
+        if rescan_looks_truncated(existing, items.len(), min_rescan_ratio) {
+            hold_truncated_rescan(pool, execution, existing, items.len(), min_rescan_ratio).await?;
+            return Ok(0);
+        }
+    }
+
+    let delete_stale = !matches!(guard, RescanGuard::KeepStale);
+    let stale =
+        db::replace_media_inventory_for_execution(pool, execution.id, &items, delete_stale).await?;
+    if !delete_stale && !stale.is_empty() {
+        info!(
+            execution_id = execution.id,
+            kept = stale.len(),
+            paths = ?stale,
+            "Stale media deletion is disabled; keeping inventory rows whose files were not found"
+        );
+    }
     db::mark_download_execution_indexed(pool, execution.id).await?;
+    Ok(items.len())
+}
+
+async fn hold_truncated_rescan(
+    pool: &SqlitePool,
+    execution: &DownloadExecutionDto,
+    existing: i64,
+    scanned: usize,
+    min_rescan_ratio: f64,
+) -> Result<(), AppError> {
+    db::mark_download_execution_rescan_held(pool, execution.id).await?;
+    if execution.rescan_held_at.is_some() {
+        debug!(
+            execution_id = execution.id,
+            existing,
+            scanned,
+            "Media rescan is still short of the indexed files; inventory stays held"
+        );
+        return Ok(());
+    }
+
+    warn!(
+        execution_id = execution.id,
+        path = %execution.target_path,
+        existing,
+        scanned,
+        min_rescan_ratio,
+        "Media rescan found far fewer files than indexed; keeping existing inventory rows"
+    );
+    db::create_download_execution_event(
+        pool,
+        db::NewDownloadExecutionEvent {
+            download_execution_id: execution.id,
+            level: "warning".to_owned(),
+            event_kind: "rescan_held".to_owned(),
+            message: format!(
+                "Rescan found {scanned} of {existing} indexed files; kept the existing inventory. Reindex the execution to accept the smaller set."
+            ),
+            downloaded_bytes: None,
+            uploaded_bytes: None,
+            download_rate_bytes: None,
+            upload_rate_bytes: None,
+            peer_count: None,
+        },
+    )
+    .await?;
+
     Ok(())
 }
 
@@ -1696,6 +1878,18 @@ fn is_active_execution_state(state: &str) -> bool {
     )
 }
 
+fn rescan_looks_truncated(existing: i64, scanned: usize, min_ratio: f64) -> bool {
+    if existing <= 0 {
+        return false;
+    }
+
+    scanned == 0 || (scanned as f64) < existing as f64 * min_ratio
+}
+
+fn is_completed_state(state: &str) -> bool {
+    matches!(state, "seeding" | "completed")
+}
+
 fn should_refresh_media_index(execution: &DownloadExecutionDto, state: &str) -> bool {
     if !matches!(state, "downloading" | "seeding" | "completed") {
         return false;
@@ -1705,11 +1899,15 @@ fn should_refresh_media_index(execution: &DownloadExecutionDto, state: &str) ->
         return true;
     }
 
-    let Some(last_indexed_at) = execution.last_indexed_at.as_deref() else {
-        return true;
-    };
-
-    let Ok(parsed) = DateTime::parse_from_rfc3339(last_indexed_at) else {
+    // A held rescan still counts as an attempt, so holds retry on the same cadence.
+    let Some(parsed) = [
+        execution.last_indexed_at.as_deref(),
+        execution.rescan_held_at.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|value| DateTime::parse_from_rfc3339(value).ok())
+    .max() else {
         return true;
     };
 
@@ -1837,7 +2035,10 @@ fn event_level_for_state(state: &str) -> &'static str {
 
 #[cfg(test)]
 mod tests {
-    use super::{map_inventory_items_for_file, new_inventory_item};
+    use super::{
+        map_inventory_items_for_file, new_inventory_item, rescan_looks_truncated,
+        should_refresh_media_index,
+    };
     use crate::{
         media::IndexedMediaFile,
         subject_parts::{SubjectPartGroup, SubjectPartSegment},
@@ -1876,6 +2077,7 @@ mod tests {
             replaced_at: None,
             failed_at: None,
             last_indexed_at: None,
+            rescan_held_at: None,
         }
     }
 
@@ -1893,6 +2095,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn held_rescan_waits_for_the_next_cadence_without_a_fresh_index() {
+        let mut execution = sample_execution();
+        execution.last_indexed_at = Some("2026-04-13T00:00:00Z".to_owned());
+        assert!(should_refresh_media_index(&execution, "downloading"));
+
+        execution.rescan_held_at = Some(chrono::Utc::now().to_rfc3339());
+        assert!(!should_refresh_media_index(&execution, "downloading"));
+    }
+
     #[test]
     fn split_part_collection_indexing_keeps_one_row_per_subject_mapping() {
         let execution = sample_execution();
@@ -1948,4 +2160,12 @@ mod tests {
         assert_eq!(items[0].bangumi_subject_id, item.bangumi_subject_id);
         assert_eq!(items[0].slot_key, item.slot_key);
     }
+
+    #[test]
+    fn truncated_rescan_keeps_existing_inventory() {
+        assert!(rescan_looks_truncated(12, 0, 0.0));
+        assert!(rescan_looks_truncated(12, 3, 0.5));
+        assert!(!rescan_looks_truncated(12, 6, 0.5));
+        assert!(!rescan_looks_truncated(0, 0, 0.5));
+    }
 }
@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::config::AuthConfig;
+
+const PRUNE_THRESHOLD: usize = 4096;
+
+pub struct LoginAttemptGuard {
+    max_failures: u32,
+    lockout: Duration,
+    attempts: Mutex<HashMap<String, AttemptState>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AttemptState {
+    failures: u32,
+    last_failure_at: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl LoginAttemptGuard {
+    pub fn new(config: &AuthConfig) -> Self {
+        Self {
+            max_failures: config.login_max_failures.max(1),
+            lockout: Duration::from_secs(config.login_lockout_secs.max(1)),
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(&self, username: &str, ip: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let Ok(attempts) = self.attempts.lock() else {
+            return Ok(());
+        };
+
+        let remaining = attempt_keys(username, ip)
+            .iter()
+            .filter_map(|key| attempts.get(key))
+            .filter_map(|state| state.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+            .max();
+
+        match remaining {
+            Some(remaining) => Err(remaining),
+            None => Ok(()),
+        }
+    }
+
+    pub fn record_failure(&self, username: &str, ip: IpAddr) {
+        let now = Instant::now();
+        let Ok(mut attempts) = self.attempts.lock() else {
+            return;
+        };
+
+        if attempts.len() >= PRUNE_THRESHOLD {
+            let lockout = self.lockout;
+            attempts.retain(|_, state| now.duration_since(state.last_failure_at) < lockout);
+        }
+
+        for key in attempt_keys(username, ip) {
+            let state = attempts.entry(key).or_insert(AttemptState {
+                failures: 0,
+                last_failure_at: now,
+                locked_until: None,
+            });
+
+            if now.duration_since(state.last_failure_at) >= self.lockout
+                || state.locked_until.is_some_and(|until| until <= now)
+            {
+                state.failures = 0;
+                state.locked_until = None;
+            }
+
+            state.failures += 1;
+            state.last_failure_at = now;
+            if state.failures >= self.max_failures {
+                state.locked_until = Some(now + self.lockout);
+            }
+        }
+    }
+
+    /// Clears the username's counter after a successful login. The IP counter is left to expire
+    /// on its own window so that logging into an owned account cannot reset it between guesses.
+    pub fn reset(&self, username: &str) {
+        if let Ok(mut attempts) = self.attempts.lock() {
+            attempts.remove(&user_key(username));
+        }
+    }
+}
+
+fn attempt_keys(username: &str, ip: IpAddr) -> [String; 2] {
+    [user_key(username), format!("ip:{ip}")]
+}
+
+fn user_key(username: &str) -> String {
+    format!("user:{}", username.trim().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoginAttemptGuard;
+    use crate::config::AppConfig;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn guard(max_failures: u32) -> LoginAttemptGuard {
+        let mut config = AppConfig::default().auth;
+        config.login_max_failures = max_failures;
+        config.login_lockout_secs = 60;
+        LoginAttemptGuard::new(&config)
+    }
+
+    #[test]
+    fn locks_after_repeated_failures_and_resets_on_success() {
+        let guard = guard(3);
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        for _ in 0..2 {
+            guard.record_failure("alice", ip);
+        }
+        assert!(guard.check("alice", ip).is_ok());
+
+        guard.record_failure("alice", ip);
+        assert!(guard.check("alice", ip).is_err());
+
+        guard.reset("alice");
+        assert!(
+            guard
+                .check("alice", IpAddr::V4(Ipv4Addr::new(10, 0, 0, 9)))
+                .is_ok()
+        );
+        assert!(guard.check("alice", ip).is_err());
+    }
+
+    #[test]
+    fn successful_login_does_not_reset_ip_counter() {
+        let guard = guard(3);
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7));
+
+        guard.record_failure("alice", ip);
+        guard.record_failure("bob", ip);
+        guard.reset("mallory");
+        assert!(guard.check("carol", ip).is_ok());
+
+        guard.record_failure("carol", ip);
+        assert!(guard.check("dave", ip).is_err());
+    }
+
+    #[test]
+    fn lockout_applies_per_ip_across_usernames() {
+        let guard = guard(2);
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7));
+
+        guard.record_failure("alice", ip);
+        guard.record_failure("bob", ip);
+
+        assert!(guard.check("carol", ip).is_err());
+        assert!(
+            guard
+                .check("carol", IpAddr::V4(Ipv4Addr::new(10, 0, 0, 8)))
+                .is_ok()
+        );
+    }
+}
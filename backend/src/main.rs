@@ -1,13 +1,19 @@
 mod animegarden;
 mod auth;
 mod bangumi;
+mod build_info;
 mod catalog_cache;
 mod config;
+mod covers;
 mod db;
 mod discovery;
 mod downloads;
 mod logcodec;
+mod login_guard;
+mod matching;
 mod media;
+mod notifications;
+mod openapi;
 mod routes;
 mod season_catalog;
 mod subject_parts;
@@ -20,8 +26,9 @@ use anicargo_downloader::{
     build_router as build_downloader_router, start_embedded as start_embedded_downloader,
 };
 use anyhow::Context;
+use axum_server::tls_rustls::RustlsConfig;
 use chrono::{FixedOffset, Utc};
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 use tokio::signal;
 use tokio::time::{self, Duration, MissedTickBehavior};
 use tracing::warn;
@@ -29,13 +36,15 @@ use tracing::warn;
 use crate::{
     animegarden::AnimeGardenClient,
     bangumi::BangumiClient,
-    config::AppConfig,
+    config::{AppConfig, TlsConfig},
     db::connect_and_migrate,
     discovery::ResourceDiscoveryCoordinator,
     downloads::{
         DownloadCoordinator, DownloadRuntimeSettings, EmbeddedDownloaderEngine,
         PlanningDownloadEngine, RqbitDownloadEngine,
     },
+    login_guard::LoginAttemptGuard,
+    notifications::WebhookNotifier,
     routes::AppState,
     telemetry::RuntimeMetrics,
     yuc::YucClient,
@@ -48,6 +57,12 @@ async fn main() -> anyhow::Result<()> {
     let (_telemetry_guards, log_file_path) =
         telemetry::init_tracing(&config.telemetry, terminal_ui_active)
             .context("failed to initialize telemetry")?;
+    // Fail on a bad certificate before any port is bound or background worker starts.
+    let tls = if config.tls.is_enabled() {
+        Some(load_tls_config(&config.tls).await?)
+    } else {
+        None
+    };
     let pool = connect_and_migrate(&config)
         .await
         .context("failed to initialize database")?;
@@ -65,8 +80,10 @@ async fn main() -> anyhow::Result<()> {
 
     let bangumi = BangumiClient::new(&config.bangumi).context("failed to initialize bangumi")?;
     let yuc = YucClient::new(&config.yuc).context("failed to initialize yuc")?;
-    let animegarden =
-        AnimeGardenClient::new(&config.animegarden).context("failed to initialize animegarden")?;
+    let parser =
+        Arc::new(media::build_parser(&config.parser).context("failed to initialize parser")?);
+    let animegarden = AnimeGardenClient::new(&config.animegarden, parser.clone())
+        .context("failed to initialize animegarden")?;
     let download_runtime_settings = DownloadRuntimeSettings::new(
         config.torrent.max_concurrent_downloads,
         config.torrent.upload_limit_mb,
@@ -77,11 +94,17 @@ async fn main() -> anyhow::Result<()> {
     let download_engine = build_download_engine(&config, downloader_service.clone())
         .await
         .context("failed to initialize download engine")?;
+    let webhook_notifier = WebhookNotifier::from_config(&config.notifications)
+        .context("failed to initialize webhook notifier")?;
     let downloads = DownloadCoordinator::new(
         download_engine,
         download_runtime_settings,
         Some(bangumi.clone()),
-    );
+        parser.clone(),
+    )
+    .with_notifier(webhook_notifier)
+    .with_min_rescan_ratio(config.storage.min_rescan_ratio)
+    .with_delete_stale_media(config.storage.delete_stale_media);
     downloads
         .apply_runtime_settings(download_runtime_settings)
         .await
@@ -98,9 +121,12 @@ async fn main() -> anyhow::Result<()> {
         pool: pool.clone(),
         bangumi,
         yuc,
+        parser,
         downloads: downloads_for_app,
         discovery,
         metrics: metrics.clone(),
+        login_guard: Arc::new(LoginAttemptGuard::new(&config.auth)),
+        subject_refreshes: Arc::default(),
     });
     spawn_download_sync_loop(
         downloads.clone(),
@@ -122,12 +148,34 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(&address)
         .await
         .with_context(|| format!("failed to bind server on {}", address))?;
+    let app = router.into_make_service_with_connect_info::<SocketAddr>();
+
+    if let Some(tls) = tls {
+        let handle = axum_server::Handle::new();
+        let shutdown_handle = handle.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            shutdown_handle.graceful_shutdown(None);
+        });
 
-    tracing::info!("Anicargo backend listening on http://{}", address);
-    axum::serve(listener, router)
-        .with_graceful_shutdown(shutdown_signal())
+        tracing::info!("Anicargo backend listening on https://{}", address);
+        axum_server::from_tcp_rustls(
+            listener
+                .into_std()
+                .context("failed to prepare listener for TLS")?,
+            tls,
+        )
+        .handle(handle)
+        .serve(app)
         .await
         .context("server exited unexpectedly")?;
+    } else {
+        tracing::info!("Anicargo backend listening on http://{}", address);
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown_signal())
+            .await
+            .context("server exited unexpectedly")?;
+    }
 
     tracing::info!("Anicargo backend stopped");
     Ok(())
@@ -220,6 +268,8 @@ async fn spawn_optional_downloader_api(
     })))
 }
 
+const DOWNLOAD_SYNC_WORKER_ID: &str = "download-sync";
+
 fn spawn_download_sync_loop(
     downloads: DownloadCoordinator,
     pool: sqlx::SqlitePool,
@@ -233,13 +283,22 @@ fn spawn_download_sync_loop(
         loop {
             interval.tick().await;
 
+            // Beat on both sides of the pass so a long but healthy sync is not reported stale.
+            record_download_sync_heartbeat(&pool).await;
             if let Err(error) = downloads.sync_active_executions(&pool, &media_root).await {
                 warn!(error = %error, "Download execution sync loop failed");
             }
+            record_download_sync_heartbeat(&pool).await;
         }
     });
 }
 
+async fn record_download_sync_heartbeat(pool: &sqlx::SqlitePool) {
+    if let Err(error) = db::record_worker_heartbeat(pool, DOWNLOAD_SYNC_WORKER_ID).await {
+        warn!(error = %error, "Failed to record download sync heartbeat");
+    }
+}
+
 fn spawn_current_season_refresh_loop(
     yuc: YucClient,
     bangumi: BangumiClient,
@@ -282,6 +341,19 @@ fn next_tokyo_midnight_delay() -> Duration {
     Duration::from_secs(wait_seconds)
 }
 
+async fn load_tls_config(config: &TlsConfig) -> anyhow::Result<RustlsConfig> {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    RustlsConfig::from_pem_file(&config.cert_path, &config.key_path)
+        .await
+        .with_context(|| {
+            format!(
+                "failed to load TLS certificate {} and key {}",
+                config.cert_path.display(),
+                config.key_path.display()
+            )
+        })
+}
+
 async fn shutdown_signal() {
     #[cfg(windows)]
     {
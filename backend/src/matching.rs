@@ -0,0 +1,204 @@
+//! Title and air-date matching helpers shared by the catalog, season and Yuc matchers.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::bangumi::AirDate;
+
+pub fn normalize_title(value: &str) -> String {
+    value
+        .chars()
+        .filter(|character| character.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Scores two raw titles the way the matchers score a normalized pair, for import scripts
+/// and tests that check naming conventions.
+#[allow(dead_code)]
+pub fn title_similarity(left: &str, right: &str) -> i32 {
+    let left = normalize_title(left);
+    let right = normalize_title(right);
+    score_text_pair(&left, &left, &right, &right)
+}
+
+pub fn score_text_pair(
+    left_normalized: &str,
+    left_stripped: &str,
+    right_normalized: &str,
+    right_stripped: &str,
+) -> i32 {
+    let mut score = 0;
+
+    if !left_normalized.is_empty() && left_normalized == right_normalized {
+        score = score.max(140);
+    }
+
+    if !left_stripped.is_empty() && left_stripped == right_stripped {
+        score = score.max(136);
+    }
+
+    if !left_stripped.is_empty() && !right_stripped.is_empty() {
+        let containment = [
+            containment_ratio(left_normalized, right_stripped),
+            containment_ratio(right_normalized, left_stripped),
+            containment_ratio(left_stripped, right_stripped),
+            containment_ratio(right_stripped, left_stripped),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(0.0_f64, f64::max);
+
+        if containment > 0.0 {
+            score = score.max((108.0 * containment.sqrt()).round() as i32);
+        }
+    }
+
+    score = score.max((dice_coefficient(left_normalized, right_normalized) * 100.0).round() as i32);
+    score = score.max((dice_coefficient(left_stripped, right_stripped) * 112.0).round() as i32);
+
+    score
+}
+
+pub fn dice_coefficient(left: &str, right: &str) -> f32 {
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    if left == right {
+        return 1.0;
+    }
+
+    let left_pairs = bigrams(left);
+    let right_pairs = bigrams(right);
+    if left_pairs.is_empty() || right_pairs.is_empty() {
+        return 0.0;
+    }
+
+    let mut overlap = 0usize;
+    let mut counts = HashMap::new();
+    for pair in &left_pairs {
+        *counts.entry(pair.clone()).or_insert(0usize) += 1;
+    }
+
+    for pair in &right_pairs {
+        if let Some(count) = counts.get_mut(pair)
+            && *count > 0
+        {
+            *count -= 1;
+            overlap += 1;
+        }
+    }
+
+    (2 * overlap) as f32 / (left_pairs.len() + right_pairs.len()) as f32
+}
+
+fn containment_ratio(haystack: &str, needle: &str) -> Option<f64> {
+    if needle.is_empty() || !haystack.contains(needle) {
+        return None;
+    }
+
+    let haystack_len = haystack.chars().count().max(1);
+    Some((needle.chars().count() as f64 / haystack_len as f64).min(1.0))
+}
+
+/// Rewards a Bangumi subject whose air date is close to the catalog entry's and
+/// penalises distant ones, comparing only as precisely as the subject date allows.
+pub fn adjust_score_for_air_date(base_score: f64, entry_date: NaiveDate, subject: AirDate) -> f64 {
+    if let Some(subject_date) = subject.to_naive_date() {
+        return match (subject_date - entry_date).num_days().abs() {
+            0 => base_score + 84.0,
+            1..=3 => base_score + 56.0,
+            4..=10 => base_score + 28.0,
+            11..=21 => base_score + 8.0,
+            22..=45 => (base_score - 18.0).max(0.0),
+            _ => (base_score - 72.0).max(0.0),
+        };
+    }
+
+    if let Some(month) = subject.month {
+        let subject_months = subject.year * 12 + month as i32;
+        let entry_months = entry_date.year() * 12 + entry_date.month() as i32;
+        return match (subject_months - entry_months).abs() {
+            0 => base_score + 28.0,
+            1 => base_score + 8.0,
+            2 => (base_score - 18.0).max(0.0),
+            _ => (base_score - 72.0).max(0.0),
+        };
+    }
+
+    match (subject.year - entry_date.year()).abs() {
+        0 => base_score,
+        1 => (base_score - 18.0).max(0.0),
+        _ => (base_score - 72.0).max(0.0),
+    }
+}
+
+fn bigrams(value: &str) -> Vec<String> {
+    let chars = value.chars().collect::<Vec<_>>();
+    if chars.len() < 2 {
+        return Vec::new();
+    }
+
+    chars
+        .windows(2)
+        .map(|window| window.iter().collect::<String>())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adjust_score_for_air_date, normalize_title, score_text_pair, title_similarity};
+    use crate::bangumi::AirDate;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn normalize_title_drops_punctuation_and_case() {
+        assert_eq!(normalize_title("Re:Zero - Season 2"), "rezeroseason2");
+    }
+
+    #[test]
+    fn title_similarity_ignores_punctuation_and_case() {
+        assert_eq!(
+            title_similarity("Sousou no Frieren", "sousou-no-frieren"),
+            140
+        );
+        assert!(title_similarity("Frieren", "Spy x Family") < 40);
+    }
+
+    #[test]
+    fn short_substring_scores_below_close_full_title() {
+        let substring = score_text_pair("one", "one", "onepunchman", "onepunchman");
+        let close = score_text_pair("onepunchmen", "onepunchmen", "onepunchman", "onepunchman");
+        assert!(substring < close, "substring {substring} vs close {close}");
+        assert!(substring < 68);
+    }
+
+    #[test]
+    fn exact_and_near_complete_containment_keep_high_scores() {
+        assert_eq!(
+            score_text_pair("frieren", "frieren", "frieren", "frieren"),
+            140
+        );
+        let near = score_text_pair(
+            "sousounofrieren",
+            "sousounofrieren",
+            "sousounofrieren2",
+            "sousounofrieren2",
+        );
+        assert!(near >= 100);
+    }
+
+    #[test]
+    fn partial_air_dates_compare_at_their_precision() {
+        let entry = NaiveDate::from_ymd_opt(2025, 10, 3).unwrap();
+        let adjust =
+            |value: &str| adjust_score_for_air_date(100.0, entry, AirDate::parse(value).unwrap());
+
+        assert_eq!(adjust("2025-10-03"), 184.0);
+        assert_eq!(adjust("2025-10"), 128.0);
+        assert_eq!(adjust("2025-07"), 28.0);
+        assert_eq!(adjust("2025"), 100.0);
+        assert_eq!(adjust("2023"), 28.0);
+    }
+}
@@ -6,12 +6,14 @@ use std::{
 };
 
 use anicargo_metadata_parser::{
-    EpisodeDescriptor, EpisodeNumber, EpisodeRangeDescriptor, ParseResult, parse_file_name,
-    parse_release_name,
+    EpisodeDescriptor, EpisodeNumber, EpisodeRangeDescriptor, ParseOptions, ParseResult, Parser,
 };
 use anyhow::Context;
 use regex::Regex;
 use serde::Deserialize;
+use tracing::{Span, debug, field, instrument, warn};
+
+use crate::config::ParserConfig;
 
 #[derive(Debug, Clone)]
 pub struct ParsedReleaseSlot {
@@ -47,13 +49,28 @@ pub struct PreparedSubtitleAsset {
     pub path: PathBuf,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootedPathError {
+    OutsideRoot,
+    NotReady,
+}
+
+pub fn build_parser(config: &ParserConfig) -> anyhow::Result<Parser> {
+    Parser::new(ParseOptions {
+        ignored_tokens: config.ignored_tokens.clone(),
+        title_strip_patterns: config.title_strip_patterns.clone(),
+    })
+    .context("invalid parser.title_strip_patterns")
+}
+
 pub fn infer_release_slot(
+    parser: &Parser,
     title: &str,
     release_type: &str,
     provider_resource_id: &str,
     release_status: &str,
 ) -> ParsedReleaseSlot {
-    let parsed = parse_release_name(title);
+    let parsed = parser.parse_release_name(title);
     if let Some(slot) = slot_from_parse(&parsed) {
         return slot;
     }
@@ -61,7 +78,9 @@ pub fn infer_release_slot(
     infer_release_slot_fallback(title, release_type, provider_resource_id, release_status)
 }
 
+#[instrument(level = "debug", skip_all, fields(root = %root.display(), files = field::Empty))]
 pub fn scan_video_files(
+    parser: &Parser,
     root: &Path,
     fallback_slot: &ParsedReleaseSlot,
 ) -> anyhow::Result<Vec<IndexedMediaFile>> {
@@ -70,6 +89,7 @@ pub fn scan_video_files(
     }
 
     let mut files = Vec::new();
+    let mut skipped_non_utf8 = 0_usize;
     let mut stack = vec![root.to_path_buf()];
 
     while let Some(current) = stack.pop() {
@@ -102,6 +122,11 @@ pub fn scan_video_files(
                 continue;
             }
 
+            let Some(absolute_path) = path.to_str().map(str::to_owned) else {
+                skipped_non_utf8 += 1;
+                continue;
+            };
+
             let metadata = entry.metadata().with_context(|| {
                 format!("failed to read metadata for media file {}", path.display())
             })?;
@@ -115,12 +140,18 @@ pub fn scan_video_files(
                 .unwrap_or(&path)
                 .to_string_lossy()
                 .replace('\\', "/");
-            let inferred_slot = infer_file_slot(&file_name, fallback_slot);
+            let inferred_slot = infer_file_slot(parser, &file_name, fallback_slot);
+            debug!(
+                relative_path = %relative_path,
+                slot_key = %inferred_slot.slot_key,
+                episode_index = ?inferred_slot.episode_index,
+                "Indexed media file"
+            );
 
             files.push(IndexedMediaFile {
                 slot_key: inferred_slot.slot_key.clone(),
                 relative_path,
-                absolute_path: path.to_string_lossy().into_owned(),
+                absolute_path,
                 file_name,
                 file_ext,
                 size_bytes: i64::try_from(metadata.len()).unwrap_or(i64::MAX),
@@ -131,7 +162,16 @@ pub fn scan_video_files(
         }
     }
 
+    if skipped_non_utf8 > 0 {
+        warn!(
+            root = %root.display(),
+            skipped = skipped_non_utf8,
+            "Skipped media files with non-UTF-8 paths"
+        );
+    }
+
     files.sort_by(|left, right| left.relative_path.cmp(&right.relative_path));
+    Span::current().record("files", files.len());
     Ok(files)
 }
 
@@ -230,6 +270,50 @@ pub fn materialize_subtitle_track(
     Ok(PreparedSubtitleAsset { path: output_path })
 }
 
+pub fn ensure_within_root(root: &Path, candidate: &Path) -> Result<PathBuf, RootedPathError> {
+    let root = std::path::absolute(root)
+        .map(|path| normalize_lexically(&path))
+        .map_err(|_| RootedPathError::OutsideRoot)?;
+    let candidate = std::path::absolute(root.join(candidate))
+        .map(|path| normalize_lexically(&path))
+        .map_err(|_| RootedPathError::OutsideRoot)?;
+
+    if !candidate.starts_with(&root) {
+        return Err(RootedPathError::OutsideRoot);
+    }
+
+    if !candidate.exists() {
+        return Err(RootedPathError::NotReady);
+    }
+
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|_| RootedPathError::OutsideRoot)?;
+    let canonical_candidate = candidate
+        .canonicalize()
+        .map_err(|_| RootedPathError::NotReady)?;
+
+    if !canonical_candidate.starts_with(&canonical_root) {
+        return Err(RootedPathError::OutsideRoot);
+    }
+
+    Ok(canonical_candidate)
+}
+
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
 pub fn parse_embedded_track_id(track_id: &str) -> anyhow::Result<i32> {
     let raw = track_id
         .strip_prefix("stream-")
@@ -238,8 +322,12 @@ pub fn parse_embedded_track_id(track_id: &str) -> anyhow::Result<i32> {
         .with_context(|| format!("invalid subtitle stream index in track id '{track_id}'"))
 }
 
-fn infer_file_slot(file_name: &str, fallback_slot: &ParsedReleaseSlot) -> ParsedReleaseSlot {
-    let parsed = parse_file_name(file_name);
+fn infer_file_slot(
+    parser: &Parser,
+    file_name: &str,
+    fallback_slot: &ParsedReleaseSlot,
+) -> ParsedReleaseSlot {
+    let parsed = parser.parse_file_name(file_name);
     if let Some(slot) = slot_from_parse(&parsed) {
         return slot;
     }
@@ -599,14 +687,18 @@ fn suffix_episode_regex() -> &'static Regex {
 
 #[cfg(test)]
 mod tests {
-    use super::{extract_collection_span, infer_release_slot, scan_video_files, slot_from_parse};
+    use super::{
+        RootedPathError, ensure_within_root, extract_collection_span, infer_release_slot,
+        scan_video_files, slot_from_parse,
+    };
     use crate::media::ParsedReleaseSlot;
-    use anicargo_metadata_parser::{parse_file_name, parse_release_name};
+    use anicargo_metadata_parser::{Parser, parse_file_name, parse_release_name};
     use std::{fs, io::Write};
 
     #[test]
     fn parser_prefers_local_episode_alias_over_absolute_number() {
         let slot = infer_release_slot(
+            &Parser::default(),
             "[LoliHouse] Tensei Shitara Slime Datta Ken 3rd Season - 24(72) [WebRip 1080p]",
             "single",
             "example",
@@ -652,7 +744,7 @@ mod tests {
             episode_end_index: None,
             is_collection: true,
         };
-        let indexed = scan_video_files(&root, &fallback).expect("scan media");
+        let indexed = scan_video_files(&Parser::default(), &root, &fallback).expect("scan media");
         assert_eq!(indexed.len(), 1);
         assert_eq!(indexed[0].episode_index, Some(24.0));
         assert_eq!(indexed[0].episode_end_index, Some(24.0));
@@ -661,6 +753,31 @@ mod tests {
         fs::remove_dir_all(&root).expect("cleanup temp root");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn file_indexing_skips_non_utf8_names_without_failing() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let root = std::env::temp_dir().join(format!("anicargo-non-utf8-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("create temp root");
+        fs::write(root.join("Show - 01.mkv"), b"test").expect("write video");
+        fs::write(root.join(OsStr::from_bytes(b"Show - 02 \xff.mkv")), b"test")
+            .expect("write non-utf8 video");
+
+        let fallback = ParsedReleaseSlot {
+            slot_key: "batch:test".to_owned(),
+            episode_index: None,
+            episode_end_index: None,
+            is_collection: true,
+        };
+        let indexed = scan_video_files(&Parser::default(), &root, &fallback).expect("scan media");
+        assert_eq!(indexed.len(), 1);
+        assert_eq!(indexed[0].file_name, "Show - 01.mkv");
+
+        fs::remove_dir_all(&root).expect("cleanup temp root");
+    }
+
     #[test]
     fn file_parser_can_read_fractional_recap_alias() {
         let parsed = parse_file_name(
@@ -670,4 +787,45 @@ mod tests {
         assert_eq!(slot.episode_index, Some(48.5));
         assert_eq!(slot.slot_key, "episode:48.5");
     }
+
+    #[test]
+    fn root_check_rejects_parent_traversal() {
+        let root = std::env::temp_dir().join(format!("anicargo-root-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("show")).expect("create temp root");
+        fs::write(root.join("show/01.mkv"), b"test").expect("write video");
+
+        assert!(ensure_within_root(&root, &root.join("show/01.mkv")).is_ok());
+        assert_eq!(
+            ensure_within_root(&root, &root.join("show/../../etc/passwd")),
+            Err(RootedPathError::OutsideRoot)
+        );
+        assert_eq!(
+            ensure_within_root(&root, &root.join("show/02.mkv")),
+            Err(RootedPathError::NotReady)
+        );
+
+        fs::remove_dir_all(&root).expect("cleanup temp root");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn root_check_rejects_symlink_escape() {
+        let base = std::env::temp_dir().join(format!("anicargo-symlink-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let root = base.join("media");
+        let outside = base.join("outside");
+        fs::create_dir_all(&root).expect("create media root");
+        fs::create_dir_all(&outside).expect("create outside dir");
+        fs::write(outside.join("secret.mkv"), b"test").expect("write outside file");
+        std::os::unix::fs::symlink(outside.join("secret.mkv"), root.join("link.mkv"))
+            .expect("create symlink");
+
+        assert_eq!(
+            ensure_within_root(&root, &root.join("link.mkv")),
+            Err(RootedPathError::OutsideRoot)
+        );
+
+        fs::remove_dir_all(&base).expect("cleanup temp dirs");
+    }
 }
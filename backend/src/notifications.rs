@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use reqwest::Client;
+use serde::Serialize;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::{config::NotificationsConfig, types::DownloadExecutionDto};
+
+const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+const WEBHOOK_RETRY_BASE_SECS: u64 = 2;
+
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    http: Client,
+    url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecutionCompletedPayload {
+    event: &'static str,
+    execution_id: i64,
+    download_job_id: i64,
+    bangumi_subject_id: i64,
+    slot_key: String,
+    episode_index: Option<f64>,
+    episode_end_index: Option<f64>,
+    is_collection: bool,
+    state: String,
+    source_title: String,
+    source_fansub_name: Option<String>,
+    media_count: usize,
+}
+
+impl WebhookNotifier {
+    pub fn from_config(config: &NotificationsConfig) -> anyhow::Result<Option<Self>> {
+        let url = config.webhook_url.trim();
+        if url.is_empty() {
+            return Ok(None);
+        }
+
+        let http = Client::builder()
+            .timeout(Duration::from_secs(config.webhook_timeout_secs))
+            .build()
+            .context("failed to build webhook http client")?;
+
+        Ok(Some(Self {
+            http,
+            url: url.to_owned(),
+        }))
+    }
+
+    pub fn notify_execution_completed(
+        &self,
+        execution: &DownloadExecutionDto,
+        state: &str,
+        media_count: usize,
+    ) {
+        let payload = ExecutionCompletedPayload {
+            event: "download.completed",
+            execution_id: execution.id,
+            download_job_id: execution.download_job_id,
+            bangumi_subject_id: execution.bangumi_subject_id,
+            slot_key: execution.slot_key.clone(),
+            episode_index: execution.episode_index,
+            episode_end_index: execution.episode_end_index,
+            is_collection: execution.is_collection,
+            state: state.to_owned(),
+            source_title: execution.source_title.clone(),
+            source_fansub_name: execution.source_fansub_name.clone(),
+            media_count,
+        };
+
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            notifier.deliver(payload).await;
+        });
+    }
+
+    async fn deliver(&self, payload: ExecutionCompletedPayload) {
+        for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+            let result = self.http.post(&self.url).json(&payload).send().await;
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    info!(
+                        execution_id = payload.execution_id,
+                        attempt, "Delivered download completion webhook"
+                    );
+                    return;
+                }
+                Ok(response) => {
+                    warn!(
+                        execution_id = payload.execution_id,
+                        attempt,
+                        status = %response.status(),
+                        "Download completion webhook returned an unsuccessful response"
+                    );
+                }
+                Err(error) => {
+                    warn!(
+                        execution_id = payload.execution_id,
+                        attempt,
+                        error = %error,
+                        "Failed to deliver download completion webhook"
+                    );
+                }
+            }
+
+            if attempt < WEBHOOK_MAX_ATTEMPTS {
+                sleep(Duration::from_secs(WEBHOOK_RETRY_BASE_SECS.pow(attempt))).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,760 @@
+//! OpenAPI description served at `/api/openapi.json`. Operations are listed by
+//! hand; request, query and response schemas come from the `JsonSchema` derives
+//! on the DTO types.
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    build_info,
+    types::{
+        ActivateDownloadResponse, ActiveDownloadsResponse, AdminAuthResponse,
+        AdminDashboardResponse, AdminDownloadCandidatesResponse,
+        AdminDownloadExecutionEventsResponse, AdminDownloadExecutionsResponse,
+        AdminDownloadQueueRequest, AdminDownloadQueueResponse, AdminExecutionReindexResponse,
+        AdminRuntimeResponse, AdminSubjectRefreshResponse, AppError, AuthResponse,
+        BootstrapResponse, CalendarResponse, CatalogManifestResponse, CatalogPageResponse,
+        CredentialsRequest, EpisodePlaybackResponse, FansubRuleDto, ForceDownloadResponse,
+        HealthResponse, PlaybackHistoryRecordRequest, PlaybackHistoryResponse, PolicyDto,
+        ResourceLibraryRequest, ResourceLibraryResponse, ScheduleDisplayQuery, SearchRequest,
+        SearchResponse, SubjectCardDto, SubjectCharactersResponse, SubjectCollectionRequest,
+        SubjectCollectionResponse, SubjectDetailResponse, SubjectDownloadStatusDto,
+        SubjectEpisodesResponse, SubjectLookupRequest, SubjectPersonsResponse,
+        ToggleSubscriptionResponse, UpdatePolicyRequest, UpsertFansubRuleRequest, ViewerSummary,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Public,
+    Viewer,
+    User,
+    Admin,
+}
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+/// `T`'s schema, as a reference to a component unless it is inlined like
+/// `Option<T>` or `bool`.
+fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    let schema = generator.subschema_for::<T>();
+    transformed(generator, schema)
+}
+
+/// `T`'s own object schema, used to spell out query parameters.
+fn fields<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    let schema = T::json_schema(generator);
+    transformed(generator, schema)
+}
+
+/// Applies the OpenAPI 3.0 transforms that `take_definitions` applies to
+/// components, so inline schemas use `nullable` too.
+fn transformed(generator: &mut SchemaGenerator, mut schema: Schema) -> Schema {
+    for transform in generator.transforms_mut() {
+        transform.transform(&mut schema);
+    }
+    schema
+}
+
+#[derive(Clone, Copy)]
+enum Body {
+    /// `ApiEnvelope` around the schema.
+    Envelope(SchemaFn),
+    Json,
+    Binary,
+    Headers,
+}
+
+struct RouteDoc {
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    access: Access,
+    body: Body,
+    query: Option<SchemaFn>,
+    request: Option<SchemaFn>,
+}
+
+const fn route(
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    access: Access,
+    body: Body,
+) -> RouteDoc {
+    RouteDoc {
+        method,
+        path,
+        summary,
+        access,
+        body,
+        query: None,
+        request: None,
+    }
+}
+
+impl RouteDoc {
+    const fn with_query(mut self, query: SchemaFn) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    const fn with_request(mut self, request: SchemaFn) -> Self {
+        self.request = Some(request);
+        self
+    }
+}
+
+// Checked against `routes::API_ROUTES` in tests; keep docs/API.md in step too.
+const ROUTES: &[RouteDoc] = &[
+    route(
+        "get",
+        "/api/health",
+        "Health probe",
+        Access::Public,
+        Body::Envelope(schema::<HealthResponse>),
+    ),
+    route(
+        "get",
+        "/api/openapi.json",
+        "This OpenAPI document, not wrapped in an envelope",
+        Access::Public,
+        Body::Json,
+    ),
+    route(
+        "get",
+        "/api/public/bootstrap",
+        "Guest/user bootstrap payload",
+        Access::Viewer,
+        Body::Envelope(schema::<BootstrapResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/calendar",
+        "Current season calendar",
+        Access::Public,
+        Body::Envelope(schema::<CalendarResponse>),
+    )
+    .with_query(fields::<ScheduleDisplayQuery>),
+    route(
+        "get",
+        "/api/public/catalogs/manifest",
+        "Optional Yuc catalog availability",
+        Access::Public,
+        Body::Envelope(schema::<CatalogManifestResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/catalogs/{kind}",
+        "`preview` or `special` catalog page",
+        Access::Public,
+        Body::Envelope(schema::<CatalogPageResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/search",
+        "Bangumi-backed subject search",
+        Access::Public,
+        Body::Envelope(schema::<SearchResponse>),
+    )
+    .with_query(fields::<SearchRequest>),
+    route(
+        "get",
+        "/api/public/subscriptions",
+        "Current viewer subscription list",
+        Access::Viewer,
+        Body::Envelope(schema::<SubjectCollectionResponse>),
+    )
+    .with_query(fields::<SubjectCollectionRequest>),
+    route(
+        "get",
+        "/api/public/history",
+        "Current viewer playback history",
+        Access::Viewer,
+        Body::Envelope(schema::<PlaybackHistoryResponse>),
+    )
+    .with_query(fields::<ResourceLibraryRequest>),
+    route(
+        "get",
+        "/api/public/resources",
+        "Indexed resource library",
+        Access::Public,
+        Body::Envelope(schema::<ResourceLibraryResponse>),
+    )
+    .with_query(fields::<ResourceLibraryRequest>),
+    route(
+        "get",
+        "/api/public/downloads/active",
+        "Active download summary",
+        Access::Public,
+        Body::Envelope(schema::<ActiveDownloadsResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}",
+        "Subject detail + episodes + subscription state",
+        Access::Public,
+        Body::Envelope(schema::<SubjectDetailResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}/download-status",
+        "Subject-level download state",
+        Access::Public,
+        Body::Envelope(schema::<Option<SubjectDownloadStatusDto>>),
+    ),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}/cover",
+        "Locally cached subject cover image",
+        Access::Public,
+        Body::Binary,
+    ),
+    route(
+        "get",
+        "/api/public/subjects/lookup",
+        "Single Bangumi subject whose name or Chinese name matches exactly",
+        Access::Public,
+        Body::Envelope(schema::<Option<SubjectCardDto>>),
+    )
+    .with_query(fields::<SubjectLookupRequest>),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}/card",
+        "Subject card from the local Bangumi cache",
+        Access::Public,
+        Body::Envelope(schema::<SubjectCardDto>),
+    ),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}/episodes",
+        "Episode list with local availability and completeness",
+        Access::Public,
+        Body::Envelope(schema::<SubjectEpisodesResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}/persons",
+        "Bangumi staff with relation and career",
+        Access::Public,
+        Body::Envelope(schema::<SubjectPersonsResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}/characters",
+        "Bangumi characters with their voice actors",
+        Access::Public,
+        Body::Envelope(schema::<SubjectCharactersResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/subjects/{subject_id}/episodes/{episode_id}/playback",
+        "Playback readiness for one episode",
+        Access::Public,
+        Body::Envelope(schema::<EpisodePlaybackResponse>),
+    ),
+    route(
+        "get",
+        "/api/public/media/{media_id}/stream",
+        "Byte-range media streaming",
+        Access::Public,
+        Body::Binary,
+    ),
+    route(
+        "head",
+        "/api/public/media/{media_id}/stream",
+        "Media stream headers without a body",
+        Access::Public,
+        Body::Headers,
+    ),
+    route(
+        "get",
+        "/api/public/media/{media_id}/subtitles/{track_id}",
+        "Extracted subtitle track",
+        Access::Public,
+        Body::Binary,
+    ),
+    route(
+        "head",
+        "/api/public/media/{media_id}/subtitles/{track_id}",
+        "Subtitle track headers without a body",
+        Access::Public,
+        Body::Headers,
+    ),
+    route(
+        "post",
+        "/api/public/subscriptions/{subject_id}/toggle",
+        "Subscribe or unsubscribe",
+        Access::Viewer,
+        Body::Envelope(schema::<ToggleSubscriptionResponse>),
+    ),
+    route(
+        "post",
+        "/api/public/history/playback",
+        "Record playback activity",
+        Access::Viewer,
+        Body::Envelope(schema::<bool>),
+    )
+    .with_request(schema::<PlaybackHistoryRecordRequest>),
+    route(
+        "post",
+        "/api/auth/register",
+        "Create a user account",
+        Access::Public,
+        Body::Envelope(schema::<AuthResponse>),
+    )
+    .with_request(schema::<CredentialsRequest>),
+    route(
+        "post",
+        "/api/auth/login",
+        "User login",
+        Access::Public,
+        Body::Envelope(schema::<AuthResponse>),
+    )
+    .with_request(schema::<CredentialsRequest>),
+    route(
+        "get",
+        "/api/auth/me",
+        "Current user session",
+        Access::User,
+        Body::Envelope(schema::<Option<ViewerSummary>>),
+    ),
+    route(
+        "post",
+        "/api/auth/logout",
+        "End user session",
+        Access::User,
+        Body::Envelope(schema::<bool>),
+    ),
+    route(
+        "get",
+        "/api/user/media/{media_id}/download",
+        "Byte-range download of the original file",
+        Access::User,
+        Body::Binary,
+    ),
+    route(
+        "head",
+        "/api/user/media/{media_id}/download",
+        "Download headers without a body",
+        Access::User,
+        Body::Headers,
+    ),
+    route(
+        "post",
+        "/api/admin/login",
+        "Compatibility login for an admin-capable account",
+        Access::Public,
+        Body::Envelope(schema::<AdminAuthResponse>),
+    )
+    .with_request(schema::<CredentialsRequest>),
+    route(
+        "post",
+        "/api/admin/logout",
+        "End the current admin-capable session",
+        Access::Admin,
+        Body::Envelope(schema::<bool>),
+    ),
+    route(
+        "get",
+        "/api/admin/dashboard",
+        "Counts, policy, fansub rules",
+        Access::Admin,
+        Body::Envelope(schema::<AdminDashboardResponse>),
+    ),
+    route(
+        "get",
+        "/api/admin/runtime",
+        "Runtime telemetry snapshot",
+        Access::Admin,
+        Body::Envelope(schema::<AdminRuntimeResponse>),
+    ),
+    route(
+        "get",
+        "/api/admin/downloads",
+        "Download jobs, filterable by lifecycle and trigger kind",
+        Access::Admin,
+        Body::Envelope(schema::<AdminDownloadQueueResponse>),
+    )
+    .with_query(fields::<AdminDownloadQueueRequest>),
+    route(
+        "get",
+        "/api/admin/downloads/{job_id}/candidates",
+        "Candidate list for a job",
+        Access::Admin,
+        Body::Envelope(schema::<AdminDownloadCandidatesResponse>),
+    ),
+    route(
+        "get",
+        "/api/admin/downloads/{job_id}/executions",
+        "Execution instances for a job",
+        Access::Admin,
+        Body::Envelope(schema::<AdminDownloadExecutionsResponse>),
+    ),
+    route(
+        "get",
+        "/api/admin/executions/{execution_id}/events",
+        "Execution event log",
+        Access::Admin,
+        Body::Envelope(schema::<AdminDownloadExecutionEventsResponse>),
+    ),
+    route(
+        "post",
+        "/api/admin/executions/{execution_id}/reindex",
+        "Rescan an execution and replace its inventory even when files went missing",
+        Access::Admin,
+        Body::Envelope(schema::<AdminExecutionReindexResponse>),
+    ),
+    route(
+        "post",
+        "/api/admin/downloads/{job_id}/execute",
+        "Manual activation of a selected job",
+        Access::Admin,
+        Body::Envelope(schema::<ActivateDownloadResponse>),
+    ),
+    route(
+        "post",
+        "/api/admin/downloads/{subject_id}/force",
+        "Force a subject into the pipeline",
+        Access::Admin,
+        Body::Envelope(schema::<ForceDownloadResponse>),
+    ),
+    route(
+        "post",
+        "/api/admin/subjects/{subject_id}/refresh",
+        "Refetch one subject from Bangumi and update its cached card",
+        Access::Admin,
+        Body::Envelope(schema::<AdminSubjectRefreshResponse>),
+    ),
+    route(
+        "put",
+        "/api/admin/policy",
+        "Update policy values",
+        Access::Admin,
+        Body::Envelope(schema::<PolicyDto>),
+    )
+    .with_request(schema::<UpdatePolicyRequest>),
+    route(
+        "post",
+        "/api/admin/fansub-rules",
+        "Add or update fansub policy rules",
+        Access::Admin,
+        Body::Envelope(schema::<FansubRuleDto>),
+    )
+    .with_request(schema::<UpsertFansubRuleRequest>),
+];
+
+pub fn document() -> Value {
+    // Responses are described as they serialize, so `Option` fields stay required
+    // but nullable; requests and queries as they deserialize.
+    let mut responses = SchemaSettings::openapi3().for_serialize().into_generator();
+    let mut requests = SchemaSettings::openapi3()
+        .for_deserialize()
+        .into_generator();
+    let mut paths = Map::new();
+    for route in ROUTES {
+        let item = paths
+            .entry(route.path.to_owned())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(item) = item {
+            item.insert(
+                route.method.to_owned(),
+                operation(route, &mut responses, &mut requests),
+            );
+        }
+    }
+
+    let mut components = responses.take_definitions(true);
+    components.extend(requests.take_definitions(true));
+    components.insert(
+        "ApiEnvelope".to_owned(),
+        json!({
+            "type": "object",
+            "required": ["data"],
+            "properties": { "data": {} },
+        }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Anicargo API",
+            "version": build_info::VERSION,
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "deviceId": {
+                    "type": "apiKey",
+                    "in": "header",
+                    "name": "x-anicargo-device-id",
+                },
+                "userToken": {
+                    "type": "http",
+                    "scheme": "bearer",
+                },
+                "adminToken": {
+                    "type": "apiKey",
+                    "in": "header",
+                    "name": "x-anicargo-admin-token",
+                },
+            },
+            "schemas": components,
+        },
+    })
+}
+
+fn operation(
+    route: &RouteDoc,
+    responses: &mut SchemaGenerator,
+    requests: &mut SchemaGenerator,
+) -> Value {
+    let success = match route.body {
+        Body::Envelope(data) => json!({
+            "description": "Success",
+            "content": {
+                "application/json": {
+                    "schema": {
+                        "allOf": [
+                            { "$ref": "#/components/schemas/ApiEnvelope" },
+                            {
+                                "type": "object",
+                                "properties": { "data": data(responses) },
+                            },
+                        ],
+                    },
+                },
+            },
+        }),
+        Body::Json => json!({
+            "description": "Success",
+            "content": {
+                "application/json": {
+                    "schema": { "type": "object" },
+                },
+            },
+        }),
+        Body::Binary => json!({
+            "description": "File contents, honouring `Range` requests",
+            "content": {
+                "application/octet-stream": {
+                    "schema": { "type": "string", "format": "binary" },
+                },
+            },
+        }),
+        Body::Headers => json!({
+            "description": "Same `Content-Type`, `Content-Length` and `Accept-Ranges` headers as `GET`, with an empty body",
+        }),
+    };
+
+    let mut parameters = path_parameters(route.path);
+    if let Some(query) = route.query {
+        let query = query(requests);
+        let required = query
+            .get("required")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        if let Some(properties) = query.get("properties").and_then(Value::as_object) {
+            parameters.extend(properties.iter().map(|(field, schema)| {
+                json!({
+                    "name": field,
+                    "in": "query",
+                    "required": required.iter().any(|name| name == field),
+                    "schema": schema,
+                })
+            }));
+        }
+    }
+
+    let mut operation = json!({
+        "summary": route.summary,
+        "parameters": parameters,
+        "responses": {
+            "200": success,
+            "default": {
+                "description": "Error",
+                "content": {
+                    "application/json": {
+                        "schema": schema::<AppError>(responses),
+                    },
+                },
+            },
+        },
+    });
+
+    if let Some(request) = route.request {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": {
+                "application/json": {
+                    "schema": request(requests),
+                },
+            },
+        });
+    }
+
+    let security = match route.access {
+        Access::Public => None,
+        Access::Viewer => Some(json!([
+            { "deviceId": [] },
+            { "deviceId": [], "userToken": [] },
+        ])),
+        Access::User => Some(json!([{ "userToken": [] }])),
+        Access::Admin => Some(json!([{ "userToken": [] }, { "adminToken": [] }])),
+    };
+    if let (Some(security), Value::Object(operation)) = (security, &mut operation) {
+        operation.insert("security".to_owned(), security);
+    }
+
+    operation
+}
+
+fn path_parameters(path: &str) -> Vec<Value> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| {
+            let schema = if name.ends_with("_id") && name != "track_id" {
+                json!({ "type": "integer", "format": "int64" })
+            } else {
+                json!({ "type": "string" })
+            };
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": schema,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Body, ROUTES, document, schema};
+    use crate::{routes::API_ROUTES, types::AppError};
+    use schemars::generate::SchemaSettings;
+    use serde_json::{Value, json};
+    use std::collections::HashSet;
+
+    #[test]
+    fn documents_every_router_route() {
+        let documented = ROUTES
+            .iter()
+            .map(|route| (route.method, route.path))
+            .collect::<HashSet<_>>();
+        let served = API_ROUTES.iter().copied().collect::<HashSet<_>>();
+
+        let undocumented = served.difference(&documented).collect::<Vec<_>>();
+        assert!(
+            undocumented.is_empty(),
+            "routes without an OpenAPI entry: {undocumented:?}"
+        );
+        let unserved = documented.difference(&served).collect::<Vec<_>>();
+        assert!(
+            unserved.is_empty(),
+            "OpenAPI entries the router does not serve: {unserved:?}"
+        );
+    }
+
+    #[test]
+    fn resolves_every_schema_reference() {
+        fn references<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+            match value {
+                Value::Object(object) => {
+                    if let Some(Value::String(reference)) = object.get("$ref") {
+                        found.push(reference);
+                    }
+                    object.values().for_each(|value| references(value, found));
+                }
+                Value::Array(items) => items.iter().for_each(|value| references(value, found)),
+                _ => {}
+            }
+        }
+
+        let document = document();
+        let schemas = &document["components"]["schemas"];
+        let mut found = Vec::new();
+        references(&document, &mut found);
+        assert!(!found.is_empty());
+        for reference in found {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("unexpected reference {reference}"));
+            assert!(schemas[name].is_object(), "dangling reference {reference}");
+        }
+
+        assert_eq!(
+            schemas["SubjectDetailDto"]["properties"]["subjectType"]["anyOf"][0]["$ref"],
+            json!("#/components/schemas/SubjectType")
+        );
+        assert_eq!(schemas["SubjectType"]["enum"][1], json!("anime"));
+        assert_eq!(
+            schemas["HealthResponse"]["required"],
+            json!(["status", "downloadWorker", "version", "gitSha"])
+        );
+        assert_eq!(
+            schemas["CredentialsRequest"]["required"],
+            json!(["username", "password"])
+        );
+        let search = &document["paths"]["/api/public/search"]["get"]["parameters"];
+        assert!(search.as_array().is_some_and(|parameters| {
+            parameters.iter().any(|parameter| {
+                parameter["in"] == "query"
+                    && parameter["name"] == "keyword"
+                    && parameter["required"] == json!(false)
+            })
+        }));
+    }
+
+    #[test]
+    fn request_and_response_schemas_do_not_share_names() {
+        let mut responses = SchemaSettings::openapi3().for_serialize().into_generator();
+        let mut requests = SchemaSettings::openapi3()
+            .for_deserialize()
+            .into_generator();
+        schema::<AppError>(&mut responses);
+        for route in ROUTES {
+            if let Body::Envelope(data) = route.body {
+                data(&mut responses);
+            }
+            for schema in [route.query, route.request].into_iter().flatten() {
+                schema(&mut requests);
+            }
+        }
+
+        let responses = responses.take_definitions(false);
+        let shared = requests
+            .take_definitions(false)
+            .into_iter()
+            .filter(|(name, _)| responses.contains_key(name))
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert!(shared.is_empty(), "schemas used both ways: {shared:?}");
+    }
+
+    #[test]
+    fn documents_each_route_once() {
+        let mut seen = HashSet::new();
+        for route in ROUTES {
+            assert!(
+                seen.insert((route.method, route.path)),
+                "duplicate route {} {}",
+                route.method,
+                route.path
+            );
+        }
+
+        let document = document();
+        let playback = &document["paths"]["/api/public/subjects/{subject_id}/episodes/{episode_id}/playback"]
+            ["get"];
+        assert_eq!(playback["parameters"].as_array().map(Vec::len), Some(2));
+        assert_eq!(
+            playback["parameters"][1]["schema"]["type"],
+            json!("integer")
+        );
+        assert!(document["paths"]["/api/admin/policy"]["put"]["security"].is_array());
+        assert!(document["paths"]["/api/health"]["get"]["security"].is_null());
+        assert!(document["paths"]["/api/public/media/{media_id}/stream"]["head"].is_object());
+    }
+}
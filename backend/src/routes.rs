@@ -1,10 +1,11 @@
+use anicargo_metadata_parser::Parser;
 use axum::{
     Json, Router,
-    extract::{Path, Query, Request, State},
-    http::HeaderMap,
+    extract::{ConnectInfo, FromRequestParts, Path, Query, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header, request::Parts},
     middleware,
-    response::IntoResponse,
-    routing::{get, post, put},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
 };
 use chrono::{FixedOffset, NaiveDate, Utc};
 use chrono_tz::Tz;
@@ -13,8 +14,9 @@ use serde_json::Value;
 use sqlx::SqlitePool;
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
+    net::SocketAddr,
     path::{Path as FsPath, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 use tokio::time::{Duration as TokioDuration, sleep, timeout};
 use tower::ServiceExt;
@@ -23,32 +25,37 @@ use tower_http::{cors::CorsLayer, services::ServeFile, trace::TraceLayer};
 use crate::{
     animegarden::AnimeGardenSearchProfile,
     auth::{
-        AdminIdentity, ViewerIdentity, extract_admin_token, extract_device_id, extract_user_token,
+        AdminIdentity, UserIdentity, ViewerIdentity, extract_admin_token, extract_device_id,
+        extract_user_token,
     },
-    bangumi::{BangumiClient, BangumiSearchQuery, EpisodeRaw, SearchFacets, SubjectRaw},
-    catalog_cache,
+    bangumi::{AirDate, BangumiClient, BangumiSearchQuery, EpisodeRaw, SearchFacets, SubjectRaw},
+    build_info, catalog_cache,
     config::AppConfig,
-    db,
+    covers, db,
     discovery::{
         ResourceDiscoveryCoordinator, candidate_priority_key, infer_part_hint_from_texts,
         infer_season_hint_from_texts, replacement_window_elapsed,
     },
     downloads::{DownloadCoordinator, DownloadDemandInput, DownloadRuntimeSettings},
-    media, season_catalog, subject_parts,
+    login_guard::LoginAttemptGuard,
+    media, openapi, season_catalog, subject_parts,
     telemetry::{self, RuntimeMetrics},
     types::{
         ActivateDownloadResponse, ActiveDownloadDto, ActiveDownloadsResponse,
         AdminDashboardResponse, AdminDownloadCandidatesResponse,
         AdminDownloadExecutionEventsResponse, AdminDownloadExecutionsResponse,
-        AdminDownloadQueueResponse, AdminRuntimeResponse, ApiEnvelope, AppError, AuthResponse,
+        AdminDownloadQueueRequest, AdminDownloadQueueResponse, AdminExecutionReindexResponse,
+        AdminRuntimeResponse, AdminSubjectRefreshResponse, ApiEnvelope, AppError, AuthResponse,
         BootstrapResponse, CalendarResponse, CatalogManifestResponse, CatalogPageResponse,
         CredentialsRequest, DownloadExecutionDto, DownloadJobDto, EpisodePlaybackMediaDto,
-        EpisodePlaybackResponse, EpisodeSubtitleTrackDto, FansubRuleDto, ForceDownloadResponse, HealthResponse,
-        PlaybackHistoryItemDto, PlaybackHistoryRecordRequest, PlaybackHistoryResponse, PolicyDto,
-        ResourceCandidateDto, ResourceLibraryRequest, ResourceLibraryResponse, RuntimeHttpStatsDto,
-        RuntimeOverviewDto, ScheduleDisplayQuery, SearchRequest, SearchResponse, SubjectCardDto,
-        SubjectCollectionRequest, SubjectCollectionResponse, SubjectDetailDto,
-        SubjectDetailResponse, SubscriptionStateDto, ToggleSubscriptionResponse,
+        EpisodePlaybackResponse, EpisodeSubtitleTrackDto, FansubRuleDto, ForceDownloadResponse,
+        HealthResponse, PlaybackHistoryItemDto, PlaybackHistoryRecordRequest,
+        PlaybackHistoryResponse, PolicyDto, ResourceCandidateDto, ResourceLibraryRequest,
+        ResourceLibraryResponse, RuntimeHttpStatsDto, RuntimeOverviewDto, ScheduleDisplayQuery,
+        SearchRequest, SearchResponse, SubjectCardDto, SubjectCharactersResponse,
+        SubjectCollectionRequest, SubjectCollectionResponse, SubjectCompletenessDto,
+        SubjectDetailDto, SubjectDetailResponse, SubjectEpisodesResponse, SubjectLookupRequest,
+        SubjectPersonsResponse, SubjectType, SubscriptionStateDto, ToggleSubscriptionResponse,
         UpdatePolicyRequest, UpsertFansubRuleRequest, ViewerSummary,
     },
     yuc::YucClient,
@@ -60,81 +67,77 @@ pub struct AppState {
     pub pool: SqlitePool,
     pub bangumi: BangumiClient,
     pub yuc: YucClient,
+    pub parser: Arc<Parser>,
     pub downloads: DownloadCoordinator,
     pub discovery: ResourceDiscoveryCoordinator,
     pub metrics: Arc<RuntimeMetrics>,
+    pub login_guard: Arc<LoginAttemptGuard>,
+    pub subject_refreshes: Arc<Mutex<HashSet<i64>>>,
+}
+
+/// Declares every API route once, building the router and the `(method, path)`
+/// table the OpenAPI document is checked against.
+macro_rules! api_routes {
+    ($($path:literal => $($method:ident($handler:ident)).+;)*) => {
+        #[cfg(test)]
+        pub(crate) const API_ROUTES: &[(&str, &str)] = &[$($((stringify!($method), $path),)+)*];
+
+        fn api_router() -> Router<AppState> {
+            Router::new()$(.route($path, MethodRouter::new()$(.$method($handler))+))*
+        }
+    };
+}
+
+api_routes! {
+    "/api/health" => get(health);
+    "/api/openapi.json" => get(openapi_document);
+    "/api/public/bootstrap" => get(bootstrap);
+    "/api/public/calendar" => get(calendar);
+    "/api/public/catalogs/manifest" => get(catalog_manifest);
+    "/api/public/catalogs/{kind}" => get(catalog_page);
+    "/api/public/search" => get(search);
+    "/api/public/subscriptions" => get(subscriptions);
+    "/api/public/history" => get(playback_history);
+    "/api/public/resources" => get(resources);
+    "/api/public/downloads/active" => get(active_downloads);
+    "/api/public/subjects/{subject_id}/download-status" => get(subject_download_status);
+    "/api/public/subjects/{subject_id}/cover" => get(subject_cover);
+    "/api/public/subjects/{subject_id}/card" => get(subject_card);
+    "/api/public/subjects/{subject_id}/episodes" => get(subject_episodes);
+    "/api/public/subjects/{subject_id}/persons" => get(subject_persons);
+    "/api/public/subjects/{subject_id}/characters" => get(subject_characters);
+    "/api/public/subjects/{subject_id}/episodes/{episode_id}/playback" => get(episode_playback);
+    "/api/public/subjects/lookup" => get(subject_lookup);
+    "/api/public/subjects/{subject_id}" => get(subject_detail);
+    "/api/public/media/{media_id}/stream" => get(stream_media_file).head(stream_media_file);
+    "/api/public/media/{media_id}/subtitles/{track_id}" => get(stream_media_subtitle_file).head(stream_media_subtitle_file);
+    "/api/public/subscriptions/{subject_id}/toggle" => post(toggle_subscription);
+    "/api/public/history/playback" => post(record_playback_history);
+    "/api/auth/register" => post(register);
+    "/api/auth/login" => post(login);
+    "/api/auth/me" => get(current_user);
+    "/api/auth/logout" => post(logout);
+    "/api/user/media/{media_id}/download" => get(download_media_file).head(download_media_file);
+    "/api/admin/login" => post(admin_login);
+    "/api/admin/logout" => post(admin_logout);
+    "/api/admin/dashboard" => get(admin_dashboard);
+    "/api/admin/runtime" => get(admin_runtime);
+    "/api/admin/downloads" => get(admin_download_queue);
+    "/api/admin/downloads/{job_id}/execute" => post(admin_activate_download);
+    "/api/admin/downloads/{job_id}/candidates" => get(admin_download_candidates);
+    "/api/admin/downloads/{job_id}/executions" => get(admin_download_executions);
+    "/api/admin/executions/{execution_id}/events" => get(admin_download_execution_events);
+    "/api/admin/executions/{execution_id}/reindex" => post(admin_reindex_execution);
+    "/api/admin/downloads/{subject_id}/force" => post(force_download_job);
+    "/api/admin/subjects/{subject_id}/refresh" => post(admin_refresh_subject);
+    "/api/admin/policy" => put(update_policy);
+    "/api/admin/fansub-rules" => post(create_fansub_rule);
 }
 
 pub fn build_router(state: AppState) -> Router {
     let metrics = state.metrics.clone();
 
-    Router::new()
-        .route("/api/health", get(health))
-        .route("/api/public/bootstrap", get(bootstrap))
-        .route("/api/public/calendar", get(calendar))
-        .route("/api/public/catalogs/manifest", get(catalog_manifest))
-        .route("/api/public/catalogs/{kind}", get(catalog_page))
-        .route("/api/public/search", get(search))
-        .route("/api/public/subscriptions", get(subscriptions))
-        .route("/api/public/history", get(playback_history))
-        .route("/api/public/resources", get(resources))
-        .route("/api/public/downloads/active", get(active_downloads))
-        .route(
-            "/api/public/subjects/{subject_id}/download-status",
-            get(subject_download_status),
-        )
-        .route(
-            "/api/public/subjects/{subject_id}/episodes/{episode_id}/playback",
-            get(episode_playback),
-        )
-        .route("/api/public/subjects/{subject_id}", get(subject_detail))
-        .route(
-            "/api/public/media/{media_id}/stream",
-            get(stream_media_file),
-        )
-        .route(
-            "/api/public/media/{media_id}/subtitles/{track_id}",
-            get(stream_media_subtitle_file),
-        )
-        .route(
-            "/api/public/subscriptions/{subject_id}/toggle",
-            post(toggle_subscription),
-        )
-        .route(
-            "/api/public/history/playback",
-            post(record_playback_history),
-        )
-        .route("/api/auth/register", post(register))
-        .route("/api/auth/login", post(login))
-        .route("/api/auth/me", get(current_user))
-        .route("/api/auth/logout", post(logout))
-        .route("/api/admin/login", post(admin_login))
-        .route("/api/admin/logout", post(admin_logout))
-        .route("/api/admin/dashboard", get(admin_dashboard))
-        .route("/api/admin/runtime", get(admin_runtime))
-        .route("/api/admin/downloads", get(admin_download_queue))
-        .route(
-            "/api/admin/downloads/{job_id}/execute",
-            post(admin_activate_download),
-        )
-        .route(
-            "/api/admin/downloads/{job_id}/candidates",
-            get(admin_download_candidates),
-        )
-        .route(
-            "/api/admin/downloads/{job_id}/executions",
-            get(admin_download_executions),
-        )
-        .route(
-            "/api/admin/executions/{execution_id}/events",
-            get(admin_download_execution_events),
-        )
-        .route(
-            "/api/admin/downloads/{subject_id}/force",
-            post(force_download_job),
-        )
-        .route("/api/admin/policy", put(update_policy))
-        .route("/api/admin/fansub-rules", post(create_fansub_rule))
+    api_router()
         .with_state(state)
         .layer(middleware::from_fn_with_state(
             metrics,
@@ -144,10 +147,41 @@ pub fn build_router(state: AppState) -> Router {
         .layer(TraceLayer::new_for_http())
 }
 
-async fn health() -> Json<ApiEnvelope<HealthResponse>> {
-    Json(ApiEnvelope::new(HealthResponse {
-        status: "ok".to_owned(),
-    }))
+const WORKER_STALE_MIN_SECS: u64 = 60;
+const WORKER_STALE_INTERVALS: u64 = 10;
+
+async fn health(State(state): State<AppState>) -> Response {
+    let stale_secs = state
+        .config
+        .torrent
+        .sync_interval_secs
+        .saturating_mul(WORKER_STALE_INTERVALS)
+        .max(WORKER_STALE_MIN_SECS);
+    let worker_alive = db::worker_health(&state.pool, stale_secs)
+        .await
+        .unwrap_or(false);
+    let (status_code, status, download_worker) = if worker_alive {
+        (StatusCode::OK, "ok", "ok")
+    } else if state.metrics.uptime() < TokioDuration::from_secs(stale_secs) {
+        (StatusCode::OK, "starting", "starting")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded", "stale")
+    };
+
+    (
+        status_code,
+        Json(ApiEnvelope::new(HealthResponse {
+            status: status.to_owned(),
+            download_worker: download_worker.to_owned(),
+            version: build_info::VERSION.to_owned(),
+            git_sha: build_info::GIT_SHA.map(str::to_owned),
+        })),
+    )
+        .into_response()
+}
+
+async fn openapi_document() -> Json<Value> {
+    Json(openapi::document())
 }
 
 async fn bootstrap(
@@ -207,7 +241,7 @@ async fn search(
     Query(request): Query<SearchRequest>,
 ) -> Result<Json<ApiEnvelope<SearchResponse>>, AppError> {
     let page = request.page.unwrap_or(1).max(1);
-    let page_size = request.page_size.unwrap_or(20).clamp(1, 60);
+    let page_size = state.config.server.page_size(request.page_size);
     let offset = (page - 1) * page_size;
     let query = BangumiSearchQuery {
         keyword: request.keyword.trim().to_owned(),
@@ -283,7 +317,7 @@ async fn subscriptions(
     let viewer = resolve_viewer(&state.pool, &headers, &device_id).await?;
 
     let page = request.page.unwrap_or(1).max(1);
-    let page_size = request.page_size.unwrap_or(30).clamp(1, 60);
+    let page_size = state.config.server.page_size(request.page_size);
     let keyword = request.keyword.unwrap_or_default();
     let sort = normalize_collection_sort(request.sort.as_deref());
     let subscriptions = db::list_viewer_subscription_subjects(&state.pool, &viewer).await?;
@@ -319,7 +353,7 @@ async fn playback_history(
     db::touch_device(&state.pool, &device_id).await?;
     let viewer = resolve_viewer(&state.pool, &headers, &device_id).await?;
     let page = request.page.unwrap_or(1).max(1);
-    let page_size = request.page_size.unwrap_or(30).clamp(1, 60);
+    let page_size = state.config.server.page_size(request.page_size);
     let offset = (page - 1) * page_size;
     let (total, history) =
         db::list_viewer_playback_history(&state.pool, &viewer, page_size, offset).await?;
@@ -339,7 +373,7 @@ async fn resources(
     Query(request): Query<ResourceLibraryRequest>,
 ) -> Result<Json<ApiEnvelope<ResourceLibraryResponse>>, AppError> {
     let page = request.page.unwrap_or(1).max(1);
-    let page_size = request.page_size.unwrap_or(30).clamp(1, 60);
+    let page_size = state.config.server.page_size(request.page_size);
     let offset = (page - 1) * page_size;
     let (total, total_size_bytes, items) =
         db::list_resource_library_items(&state.pool, request.keyword.as_deref(), page_size, offset)
@@ -379,7 +413,7 @@ async fn active_downloads(
         db::list_visible_download_executions(&state.pool, state.downloads.engine_name(), 24)
             .await?;
     let items = normalize_visible_active_downloads(
-        hydrate_active_downloads(&state.bangumi, &state.yuc, executions).await,
+        hydrate_active_downloads(&state.bangumi, &state.yuc, &state.parser, executions).await,
         state.config.torrent.max_concurrent_downloads,
     );
 
@@ -473,8 +507,8 @@ fn relation_card_rank(relation: &str) -> i32 {
     }
 }
 
-fn include_related_subject_card(relation: &str, subject_type: i64) -> bool {
-    subject_type == 2
+fn include_related_subject_card(relation: &str, subject_type: SubjectType) -> bool {
+    subject_type == SubjectType::Anime
         && is_relation_match(
             relation,
             &[
@@ -527,7 +561,7 @@ async fn fetch_related_subject_cards(
 ) -> Vec<SubjectCardDto> {
     let related_items = related_subjects
         .iter()
-        .filter(|item| include_related_subject_card(&item.relation, item.r#type))
+        .filter(|item| include_related_subject_card(&item.relation, item.subject_type))
         .cloned()
         .collect::<Vec<_>>();
 
@@ -582,6 +616,139 @@ async fn subject_download_status(
     Ok(Json(ApiEnvelope::new(status)))
 }
 
+async fn subject_lookup(
+    State(state): State<AppState>,
+    Query(query): Query<SubjectLookupRequest>,
+) -> Result<Json<ApiEnvelope<Option<SubjectCardDto>>>, AppError> {
+    if query.name.trim().is_empty() {
+        return Err(AppError::bad_request("name is required"));
+    }
+
+    let subject = state.bangumi.find_exact(&query.name).await?;
+    Ok(Json(ApiEnvelope::new(
+        subject.map(|subject| subject.to_card()),
+    )))
+}
+
+async fn subject_card(
+    State(state): State<AppState>,
+    Path(subject_id): Path<i64>,
+) -> Result<Json<ApiEnvelope<SubjectCardDto>>, AppError> {
+    let card = catalog_cache::load_subject_card(&state.pool, &state.bangumi, subject_id).await?;
+    Ok(Json(ApiEnvelope::new(card)))
+}
+
+async fn subject_persons(
+    State(state): State<AppState>,
+    Path(subject_id): Path<i64>,
+) -> Result<Json<ApiEnvelope<SubjectPersonsResponse>>, AppError> {
+    let persons = state.bangumi.fetch_subject_persons(subject_id).await?;
+
+    Ok(Json(ApiEnvelope::new(SubjectPersonsResponse {
+        bangumi_subject_id: subject_id,
+        items: persons.iter().map(|person| person.to_dto()).collect(),
+    })))
+}
+
+async fn subject_characters(
+    State(state): State<AppState>,
+    Path(subject_id): Path<i64>,
+) -> Result<Json<ApiEnvelope<SubjectCharactersResponse>>, AppError> {
+    let characters = state.bangumi.fetch_subject_characters(subject_id).await?;
+
+    Ok(Json(ApiEnvelope::new(SubjectCharactersResponse {
+        bangumi_subject_id: subject_id,
+        items: characters
+            .iter()
+            .map(|character| character.to_dto())
+            .collect(),
+    })))
+}
+
+async fn subject_episodes(
+    State(state): State<AppState>,
+    Path(subject_id): Path<i64>,
+) -> Result<Json<ApiEnvelope<SubjectEpisodesResponse>>, AppError> {
+    let (card, episodes, episode_availability) = tokio::try_join!(
+        catalog_cache::load_subject_card(&state.pool, &state.bangumi, subject_id),
+        catalog_cache::load_subject_episodes(&state.pool, &state.bangumi, subject_id),
+        db::list_subject_episode_availability(&state.pool, subject_id)
+    )?;
+
+    let episodes = episodes
+        .into_iter()
+        .map(|episode| {
+            let (is_available, availability_note) =
+                resolve_episode_availability(&episode, &episode_availability);
+            (episode, is_available, availability_note)
+        })
+        .collect::<Vec<_>>();
+    let completeness = summarize_completeness(card.total_episodes, &episodes);
+
+    Ok(Json(ApiEnvelope::new(SubjectEpisodesResponse {
+        bangumi_subject_id: subject_id,
+        episodes: episodes
+            .into_iter()
+            .map(|(episode, is_available, availability_note)| {
+                episode.to_dto(is_available, availability_note)
+            })
+            .collect(),
+        completeness,
+    })))
+}
+
+fn summarize_completeness(
+    total_episodes: Option<i64>,
+    episodes: &[(EpisodeRaw, bool, Option<String>)],
+) -> SubjectCompletenessDto {
+    let numbered = episodes
+        .iter()
+        .filter_map(|(episode, is_available, _)| {
+            episode
+                .preferred_episode_number()
+                .map(|number| (episode, number, *is_available))
+        })
+        .collect::<Vec<_>>();
+    let available_episodes = numbered
+        .iter()
+        .filter(|(_, _, is_available)| *is_available)
+        .count() as i64;
+    let total_episodes = total_episodes
+        .filter(|total| *total > 0)
+        .or_else(|| (!numbered.is_empty()).then_some(numbered.len() as i64));
+    let missing_episode_numbers = numbered
+        .iter()
+        .filter(|(episode, _, is_available)| !is_available && episode_has_aired(episode))
+        .map(|(_, number, _)| *number)
+        .collect();
+
+    SubjectCompletenessDto {
+        total_episodes,
+        available_episodes,
+        ratio: total_episodes.map(|total| (available_episodes as f64 / total as f64).min(1.0)),
+        missing_episode_numbers,
+    }
+}
+
+async fn subject_cover(
+    State(state): State<AppState>,
+    Path(subject_id): Path<i64>,
+    request: Request,
+) -> Result<impl IntoResponse, AppError> {
+    let cover_path = covers::cache_subject_cover(
+        &state.pool,
+        &state.bangumi,
+        &state.config.storage.cache_root,
+        subject_id,
+    )
+    .await?;
+
+    ServeFile::new(cover_path)
+        .oneshot(request)
+        .await
+        .map_err(|_| AppError::internal("failed to serve subject cover"))
+}
+
 async fn episode_playback(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -684,10 +851,7 @@ async fn stream_media_file(
         .await?
         .ok_or_else(|| AppError::not_found("media item not found"))?;
 
-    let path = PathBuf::from(&media.absolute_path);
-    if !path.exists() {
-        return Err(AppError::not_found("media file not found on disk"));
-    }
+    let path = resolve_media_path(&state.config.storage.media_root, &media.absolute_path)?;
 
     ServeFile::new(path)
         .oneshot(request)
@@ -695,6 +859,65 @@ async fn stream_media_file(
         .map_err(|_| AppError::internal("failed to stream media file"))
 }
 
+async fn download_media_file(
+    State(state): State<AppState>,
+    user: UserIdentity,
+    Path(media_id): Path<i64>,
+    request: Request,
+) -> Result<Response, AppError> {
+    let media = db::resource_library_item_by_id(&state.pool, media_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("media item not found"))?;
+    tracing::debug!(
+        user_id = user.id,
+        username = %user.username,
+        media_id,
+        "Serving media download"
+    );
+
+    let path = resolve_media_path(&state.config.storage.media_root, &media.absolute_path)?;
+
+    let mut response = ServeFile::new(path)
+        .oneshot(request)
+        .await
+        .map_err(|_| AppError::internal("failed to serve media download"))?
+        .into_response();
+    if let Ok(value) = HeaderValue::from_str(&attachment_disposition(&media.file_name)) {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    Ok(response)
+}
+
+fn attachment_disposition(file_name: &str) -> String {
+    let fallback = file_name
+        .chars()
+        .map(|character| {
+            if character == ' '
+                || (character.is_ascii_graphic() && !matches!(character, '"' | '\\'))
+            {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let encoded = file_name
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect::<String>();
+
+    format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
 async fn stream_media_subtitle_file(
     State(state): State<AppState>,
     Path((media_id, track_id)): Path<(i64, String)>,
@@ -704,10 +927,7 @@ async fn stream_media_subtitle_file(
         .await?
         .ok_or_else(|| AppError::not_found("media item not found"))?;
 
-    let path = PathBuf::from(&media.absolute_path);
-    if !path.exists() {
-        return Err(AppError::not_found("media file not found on disk"));
-    }
+    let path = resolve_media_path(&state.config.storage.media_root, &media.absolute_path)?;
 
     let subtitle_asset =
         media::materialize_subtitle_track(&path, &state.config.storage.media_root, media.id, &track_id)
@@ -728,6 +948,16 @@ async fn stream_media_subtitle_file(
         .map_err(|_| AppError::internal("failed to stream subtitle file"))
 }
 
+fn resolve_media_path(media_root: &FsPath, absolute_path: &str) -> Result<PathBuf, AppError> {
+    media::ensure_within_root(media_root, FsPath::new(absolute_path)).map_err(|error| match error {
+        media::RootedPathError::NotReady => AppError::not_found("media file is not available yet"),
+        media::RootedPathError::OutsideRoot => {
+            tracing::warn!(path = %absolute_path, "Refused to serve media outside the media root");
+            AppError::not_found("media file not found on disk")
+        }
+    })
+}
+
 async fn toggle_subscription(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -740,7 +970,9 @@ async fn toggle_subscription(
     let policy = db::load_policy(&state.pool).await?;
     let (is_subscribed, actual_subscription_count) =
         db::toggle_subscription(&state.pool, &viewer, subject_id).await?;
-    let profile = resolve_subject_search_profile(&state.pool, &state.bangumi, subject_id).await;
+    let profile =
+        resolve_subject_search_profile(&state.pool, &state.bangumi, &state.parser, subject_id)
+            .await;
     let download = state
         .downloads
         .reconcile_subscription_demand(
@@ -840,16 +1072,11 @@ async fn register(
 
 async fn login(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     Json(payload): Json<CredentialsRequest>,
 ) -> Result<Json<ApiEnvelope<AuthResponse>>, AppError> {
     validate_credentials(&payload.username, &payload.password)?;
-    let (viewer, token) = db::login_user(
-        &state.pool,
-        &payload.username,
-        &payload.password,
-        &state.config.auth,
-    )
-    .await?;
+    let (viewer, token) = guarded_login(&state, peer, &payload).await?;
 
     Ok(Json(ApiEnvelope::new(AuthResponse {
         token,
@@ -857,6 +1084,39 @@ async fn login(
     })))
 }
 
+async fn guarded_login(
+    state: &AppState,
+    peer: SocketAddr,
+    payload: &CredentialsRequest,
+) -> Result<(ViewerIdentity, String), AppError> {
+    let ip = peer.ip();
+    if let Err(remaining) = state.login_guard.check(&payload.username, ip) {
+        return Err(AppError::too_many_requests(
+            "too many failed login attempts, try again later",
+            remaining.as_secs().max(1),
+        ));
+    }
+
+    match db::login_user(
+        &state.pool,
+        &payload.username,
+        &payload.password,
+        &state.config.auth,
+    )
+    .await
+    {
+        Ok(result) => {
+            state.login_guard.reset(&payload.username);
+            Ok(result)
+        }
+        Err(error @ AppError::Unauthorized(_)) => {
+            state.login_guard.record_failure(&payload.username, ip);
+            Err(error)
+        }
+        Err(error) => Err(error),
+    }
+}
+
 async fn current_user(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -887,16 +1147,11 @@ async fn logout(
 
 async fn admin_login(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     Json(payload): Json<CredentialsRequest>,
 ) -> Result<Json<ApiEnvelope<crate::types::AdminAuthResponse>>, AppError> {
     validate_credentials(&payload.username, &payload.password)?;
-    let (viewer, token) = db::login_user(
-        &state.pool,
-        &payload.username,
-        &payload.password,
-        &state.config.auth,
-    )
-    .await?;
+    let (viewer, token) = guarded_login(&state, peer, &payload).await?;
 
     let ViewerIdentity::User {
         username, is_admin, ..
@@ -943,9 +1198,8 @@ async fn admin_logout(
 
 async fn admin_dashboard(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    admin: AdminIdentity,
 ) -> Result<Json<ApiEnvelope<AdminDashboardResponse>>, AppError> {
-    let admin = require_admin(&state.pool, &headers).await?;
     let policy = db::load_policy(&state.pool).await?;
     let fansub_rules = db::list_fansub_rules(&state.pool).await?;
     let counts = db::admin_counts(&state.pool).await?;
@@ -958,22 +1212,43 @@ async fn admin_dashboard(
     })))
 }
 
+/// The admin queue client does not page yet, so it keeps a larger default than the public lists.
+const ADMIN_DOWNLOAD_QUEUE_PAGE_SIZE: usize = 50;
+
 async fn admin_download_queue(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
+    Query(request): Query<AdminDownloadQueueRequest>,
 ) -> Result<Json<ApiEnvelope<AdminDownloadQueueResponse>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
-    let items = state.downloads.list_jobs(&state.pool, 50).await?;
+    let page = request.page.unwrap_or(1).max(1);
+    let page_size = state.config.server.page_size(Some(
+        request.page_size.unwrap_or(ADMIN_DOWNLOAD_QUEUE_PAGE_SIZE),
+    ));
+    let offset = (page - 1) * page_size;
+    let (total, items) = state
+        .downloads
+        .list_jobs(
+            &state.pool,
+            request.lifecycle.as_deref(),
+            request.trigger_kind.as_deref(),
+            page_size,
+            offset,
+        )
+        .await?;
 
-    Ok(Json(ApiEnvelope::new(AdminDownloadQueueResponse { items })))
+    Ok(Json(ApiEnvelope::new(AdminDownloadQueueResponse {
+        items,
+        total,
+        page,
+        page_size,
+        has_next_page: offset + page_size < total,
+    })))
 }
 
 async fn admin_runtime(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
 ) -> Result<Json<ApiEnvelope<AdminRuntimeResponse>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
-
     let snapshot = state.metrics.snapshot();
     let overview = db::runtime_overview(&state.pool).await?;
 
@@ -1014,10 +1289,9 @@ async fn admin_runtime(
 
 async fn admin_download_candidates(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
     Path(job_id): Path<i64>,
 ) -> Result<Json<ApiEnvelope<AdminDownloadCandidatesResponse>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
     let items = db::list_resource_candidates(&state.pool, job_id).await?;
 
     Ok(Json(ApiEnvelope::new(AdminDownloadCandidatesResponse {
@@ -1028,10 +1302,9 @@ async fn admin_download_candidates(
 
 async fn admin_activate_download(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
     Path(job_id): Path<i64>,
 ) -> Result<Json<ApiEnvelope<ActivateDownloadResponse>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
     let decision = state
         .downloads
         .materialize_selected_candidate(&state.pool, &state.config.storage.media_root, job_id)
@@ -1045,10 +1318,9 @@ async fn admin_activate_download(
 
 async fn admin_download_executions(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
     Path(job_id): Path<i64>,
 ) -> Result<Json<ApiEnvelope<AdminDownloadExecutionsResponse>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
     let items = state.downloads.list_executions(&state.pool, job_id).await?;
 
     Ok(Json(ApiEnvelope::new(AdminDownloadExecutionsResponse {
@@ -1059,10 +1331,9 @@ async fn admin_download_executions(
 
 async fn admin_download_execution_events(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
     Path(execution_id): Path<i64>,
 ) -> Result<Json<ApiEnvelope<AdminDownloadExecutionEventsResponse>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
     let items = db::list_download_execution_events(&state.pool, execution_id).await?;
 
     Ok(Json(ApiEnvelope::new(
@@ -1073,15 +1344,73 @@ async fn admin_download_execution_events(
     )))
 }
 
+async fn admin_reindex_execution(
+    State(state): State<AppState>,
+    _admin: AdminIdentity,
+    Path(execution_id): Path<i64>,
+) -> Result<Json<ApiEnvelope<AdminExecutionReindexResponse>>, AppError> {
+    let media_count = state
+        .downloads
+        .reindex_execution(&state.pool, execution_id)
+        .await?;
+
+    Ok(Json(ApiEnvelope::new(AdminExecutionReindexResponse {
+        download_execution_id: execution_id,
+        media_count,
+    })))
+}
+
+async fn admin_refresh_subject(
+    State(state): State<AppState>,
+    _admin: AdminIdentity,
+    Path(subject_id): Path<i64>,
+) -> Result<Json<ApiEnvelope<AdminSubjectRefreshResponse>>, AppError> {
+    let Some(_refresh) = SubjectRefreshGuard::acquire(&state.subject_refreshes, subject_id) else {
+        return Err(AppError::conflict("subject refresh is already running"));
+    };
+
+    let (card, episode_count) =
+        catalog_cache::refresh_subject_card(&state.pool, &state.bangumi, subject_id).await?;
+
+    Ok(Json(ApiEnvelope::new(AdminSubjectRefreshResponse {
+        card,
+        episode_count,
+    })))
+}
+
+struct SubjectRefreshGuard {
+    running: Arc<Mutex<HashSet<i64>>>,
+    subject_id: i64,
+}
+
+impl SubjectRefreshGuard {
+    fn acquire(running: &Arc<Mutex<HashSet<i64>>>, subject_id: i64) -> Option<Self> {
+        let mut set = running.lock().ok()?;
+        set.insert(subject_id).then(|| Self {
+            running: running.clone(),
+            subject_id,
+        })
+    }
+}
+
+impl Drop for SubjectRefreshGuard {
+    fn drop(&mut self) {
+        if let Ok(mut set) = self.running.lock() {
+            set.remove(&self.subject_id);
+        }
+    }
+}
+
 async fn force_download_job(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    admin: AdminIdentity,
     Path(subject_id): Path<i64>,
 ) -> Result<Json<ApiEnvelope<ForceDownloadResponse>>, AppError> {
-    let admin = require_admin(&state.pool, &headers).await?;
     let policy = db::load_policy(&state.pool).await?;
     let subscription_count = db::total_subscription_count(&state.pool, subject_id).await?;
-    let profile = resolve_subject_search_profile(&state.pool, &state.bangumi, subject_id).await;
+    let profile =
+        resolve_subject_search_profile(&state.pool, &state.bangumi, &state.parser, subject_id)
+            .await;
     let decision = state
         .downloads
         .reconcile_subscription_demand(
@@ -1118,11 +1447,9 @@ async fn force_download_job(
 
 async fn update_policy(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
     Json(payload): Json<UpdatePolicyRequest>,
 ) -> Result<Json<ApiEnvelope<crate::types::PolicyDto>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
-
     let policy = db::update_policy(
         &state.pool,
         payload.subscription_threshold,
@@ -1148,11 +1475,9 @@ async fn update_policy(
 
 async fn create_fansub_rule(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    _admin: AdminIdentity,
     Json(payload): Json<UpsertFansubRuleRequest>,
 ) -> Result<Json<ApiEnvelope<FansubRuleDto>>, AppError> {
-    require_admin(&state.pool, &headers).await?;
-
     let rule = db::add_fansub_rule(
         &state.pool,
         &payload.fansub_name,
@@ -1189,6 +1514,33 @@ async fn resolve_optional_viewer(
     Ok(fallback_device_id.map(|id| ViewerIdentity::Device { id: id.to_owned() }))
 }
 
+impl FromRequestParts<AppState> for AdminIdentity {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        require_admin(&state.pool, &parts.headers).await
+    }
+}
+
+impl FromRequestParts<AppState> for UserIdentity {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        require_user(&state.pool, &parts.headers).await
+    }
+}
+
+async fn require_user(pool: &SqlitePool, headers: &HeaderMap) -> Result<UserIdentity, AppError> {
+    let Some(token) = extract_user_token(headers) else {
+        return Err(AppError::unauthorized("user login required"));
+    };
+
+    match db::user_from_token(pool, &token).await? {
+        Some(ViewerIdentity::User { id, username, .. }) => Ok(UserIdentity { id, username }),
+        _ => Err(AppError::unauthorized("invalid user token")),
+    }
+}
+
 async fn require_admin(pool: &SqlitePool, headers: &HeaderMap) -> Result<AdminIdentity, AppError> {
     if let Some(token) = extract_user_token(headers) {
         if let Some(admin) = db::admin_from_user_token(pool, &token).await? {
@@ -1286,6 +1638,7 @@ async fn enrich_detail(yuc: &YucClient, detail: SubjectDetailDto) -> SubjectDeta
 async fn resolve_subject_search_profile(
     pool: &SqlitePool,
     bangumi: &BangumiClient,
+    parser: &Parser,
     subject_id: i64,
 ) -> AnimeGardenSearchProfileWithStatus {
     let cached = match db::cached_bangumi_subject_summary(pool, subject_id).await {
@@ -1328,9 +1681,9 @@ async fn resolve_subject_search_profile(
                 infer_season_hint_from_texts([subject.name.as_str(), subject.name_cn.as_str()]);
             let mut part_hint =
                 infer_part_hint_from_texts([subject.name.as_str(), subject.name_cn.as_str()]);
-            let mut aliases = subject_search_aliases(&subject);
+            let mut aliases = subject_search_aliases(parser, &subject);
             if let Ok(Some(group)) =
-                subject_parts::resolve_subject_part_group(bangumi, subject_id).await
+                subject_parts::resolve_subject_part_group(bangumi, parser, subject_id).await
             {
                 if let Some(current_segment) = subject_parts::current_segment(&group, subject_id) {
                     part_hint = Some(part_hint.unwrap_or(current_segment.part_index.max(1)));
@@ -1341,7 +1694,7 @@ async fn resolve_subject_search_profile(
                     }
                     match bangumi.fetch_subject(segment.bangumi_subject_id).await {
                         Ok(related_subject) => {
-                            aliases.extend(subject_search_aliases(&related_subject));
+                            aliases.extend(subject_search_aliases(parser, &related_subject));
                         }
                         Err(error) => {
                             tracing::warn!(
@@ -1739,6 +2092,24 @@ impl DownloadPlan {
     }
 }
 
+/// Shared handles the download planners read from.
+#[derive(Clone, Copy)]
+struct PlanningContext<'a> {
+    pool: &'a SqlitePool,
+    bangumi: Option<&'a BangumiClient>,
+    parser: &'a Parser,
+}
+
+impl<'a> PlanningContext<'a> {
+    fn from_state(state: &'a AppState) -> Self {
+        Self {
+            pool: &state.pool,
+            bangumi: Some(&state.bangumi),
+            parser: &state.parser,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct CollectionPlanning {
     skip_all: bool,
@@ -1889,8 +2260,7 @@ async fn apply_download_plan(
     ) -> Result<(), AppError> {
     let plan = if job.release_status == "airing" {
         build_airing_download_plan(
-            &state.pool,
-            Some(&state.bangumi),
+            PlanningContext::from_state(state),
             job,
             policy,
             candidates,
@@ -1899,8 +2269,7 @@ async fn apply_download_plan(
         .await?
     } else {
         build_completed_download_plan(
-            &state.pool,
-            Some(&state.bangumi),
+            PlanningContext::from_state(state),
             job,
             policy,
             candidates,
@@ -2180,14 +2549,18 @@ fn collection_stage(
 }
 
 async fn build_collection_planning(
-    pool: &SqlitePool,
-    bangumi: Option<&BangumiClient>,
+    context: PlanningContext<'_>,
     job: &crate::types::DownloadJobDto,
     candidates: &[ResourceCandidateDto],
     availability: &[db::SubjectEpisodeAvailability],
     missing_episodes: &[f64],
     preferred_fansub: Option<&str>,
 ) -> Result<CollectionPlanning, AppError> {
+    let PlanningContext {
+        pool,
+        bangumi,
+        parser,
+    } = context;
     let mut planning = CollectionPlanning::default();
     let collection_candidates = candidates
         .iter()
@@ -2202,7 +2575,7 @@ async fn build_collection_planning(
     }
 
     let split_part_group = match bangumi {
-        Some(bangumi) => match subject_parts::resolve_subject_part_group(bangumi, job.bangumi_subject_id).await {
+        Some(bangumi) => match subject_parts::resolve_subject_part_group(bangumi, parser, job.bangumi_subject_id).await {
             Ok(group) => group,
             Err(error) => {
                 tracing::warn!(
@@ -2437,13 +2810,13 @@ async fn build_collection_planning(
 }
 
 async fn build_airing_download_plan(
-    pool: &SqlitePool,
-    bangumi: Option<&BangumiClient>,
+    context: PlanningContext<'_>,
     job: &crate::types::DownloadJobDto,
     policy: &crate::types::PolicyDto,
     candidates: &[ResourceCandidateDto],
     targets: Option<&AiringEpisodeTargets>,
 ) -> Result<DownloadPlan, AppError> {
+    let pool = context.pool;
     let eligible = candidates
         .iter()
         .filter(|candidate| {
@@ -2500,8 +2873,7 @@ async fn build_airing_download_plan(
         .as_ref()
         .and_then(|candidate| candidate.fansub_name.clone());
     let collection_planning = build_collection_planning(
-        pool,
-        bangumi,
+        context,
         job,
         candidates,
         &availability,
@@ -2656,13 +3028,13 @@ async fn build_airing_download_plan(
 }
 
 async fn build_completed_download_plan(
-    pool: &SqlitePool,
-    bangumi: Option<&BangumiClient>,
+    context: PlanningContext<'_>,
     job: &crate::types::DownloadJobDto,
     policy: &crate::types::PolicyDto,
     candidates: &[ResourceCandidateDto],
     targets: Option<&AiringEpisodeTargets>,
 ) -> Result<DownloadPlan, AppError> {
+    let pool = context.pool;
     let availability = db::list_subject_episode_availability(pool, job.bangumi_subject_id).await?;
     let previous_selected =
         db::latest_selected_candidate_for_subject(pool, job.bangumi_subject_id).await?;
@@ -2683,8 +3055,7 @@ async fn build_completed_download_plan(
         .as_ref()
         .and_then(|candidate| candidate.fansub_name.as_deref());
     let collection_planning = build_collection_planning(
-        pool,
-        bangumi,
+        context,
         job,
         candidates,
         &availability,
@@ -3241,6 +3612,7 @@ async fn hydrate_playback_history(
 async fn hydrate_active_downloads(
     bangumi: &BangumiClient,
     yuc: &YucClient,
+    parser: &Parser,
     executions: Vec<crate::types::DownloadExecutionDto>,
 ) -> Vec<ActiveDownloadDto> {
     let card_map = fetch_subject_card_map(
@@ -3263,6 +3635,7 @@ async fn hydrate_active_downloads(
                 .unwrap_or_else(|| "completed".to_owned());
             let (slot_key, episode_index, episode_end_index, is_collection) =
                 sanitize_download_display_slot(
+                    parser,
                     &execution.source_title,
                     &release_status,
                     execution.is_collection,
@@ -3322,6 +3695,7 @@ fn normalize_visible_active_downloads(
 }
 
 fn sanitize_download_display_slot(
+    parser: &Parser,
     source_title: &str,
     release_status: &str,
     is_collection: bool,
@@ -3339,6 +3713,7 @@ fn sanitize_download_display_slot(
     }
 
     let reparsed = media::infer_release_slot(
+        parser,
         source_title,
         if is_collection { "batch" } else { "single" },
         &slot_key,
@@ -3414,11 +3789,12 @@ impl AnimeGardenSearchProfileWithStatus {
     }
 }
 
-fn subject_search_aliases(subject: &SubjectRaw) -> Vec<String> {
+fn subject_search_aliases(parser: &Parser, subject: &SubjectRaw) -> Vec<String> {
     let mut aliases = Vec::new();
     push_subject_alias(&mut aliases, &subject.name);
     push_subject_alias(&mut aliases, &subject.name_cn);
-    for alias in subject_parts::collect_base_title_aliases(&subject.name, &subject.name_cn) {
+    for alias in subject_parts::collect_base_title_aliases(parser, &subject.name, &subject.name_cn)
+    {
         push_subject_alias(&mut aliases, &alias);
     }
 
@@ -3566,16 +3942,7 @@ fn resolve_episode_availability(
 }
 
 fn episode_has_aired(episode: &EpisodeRaw) -> bool {
-    if episode.airdate.trim().is_empty() {
-        return true;
-    }
-
-    let date_part = episode
-        .airdate
-        .split_once('T')
-        .map(|(left, _)| left)
-        .unwrap_or(episode.airdate.as_str());
-    let Ok(airdate) = NaiveDate::parse_from_str(date_part, "%Y-%m-%d") else {
+    let Some(airdate) = episode.parsed_airdate().and_then(AirDate::to_naive_date) else {
         return true;
     };
 
@@ -3660,12 +4027,31 @@ fn format_runtime_duration(duration: std::time::Duration) -> String {
 #[cfg(test)]
 mod tests {
     use super::{
+        AppState, admin_refresh_subject, attachment_disposition,
         collection_execution_matches_split_part_group_total,
-        collection_matches_split_part_group_total, collection_matches_target_window,
-        normalize_visible_active_downloads,
+        collection_matches_split_part_group_total, collection_matches_target_window, health,
+        normalize_visible_active_downloads, summarize_completeness,
     };
+    use crate::animegarden::AnimeGardenClient;
+    use crate::auth::AdminIdentity;
+    use crate::bangumi::{BangumiClient, EpisodeRaw};
+    use crate::config::AppConfig;
+    use crate::discovery::ResourceDiscoveryCoordinator;
+    use crate::downloads::{DownloadCoordinator, DownloadRuntimeSettings, PlanningDownloadEngine};
+    use crate::login_guard::LoginAttemptGuard;
     use crate::subject_parts::{SubjectPartGroup, SubjectPartSegment};
+    use crate::telemetry::RuntimeMetrics;
     use crate::types::{ActiveDownloadDto, DownloadExecutionDto, ResourceCandidateDto};
+    use crate::yuc::YucClient;
+    use anicargo_metadata_parser::Parser;
+    use axum::{
+        extract::{Path, State},
+        http::StatusCode,
+        response::IntoResponse,
+    };
+    use serde_json::Value;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::sync::Arc;
 
     fn sample_collection_candidate(start: f64, end: f64) -> ResourceCandidateDto {
         ResourceCandidateDto {
@@ -3697,6 +4083,114 @@ mod tests {
         }
     }
 
+    fn sample_episode(ep: f64, airdate: &str) -> EpisodeRaw {
+        EpisodeRaw {
+            id: ep as i64,
+            sort: Some(ep),
+            ep: Some(ep),
+            name: String::new(),
+            name_cn: String::new(),
+            airdate: airdate.to_owned(),
+            duration_seconds: None,
+        }
+    }
+
+    fn sample_state() -> AppState {
+        let config = AppConfig::default();
+        let parser = Arc::new(Parser::default());
+        let bangumi = BangumiClient::new(&config.bangumi).expect("bangumi client");
+        let animegarden = AnimeGardenClient::new(&config.animegarden, parser.clone())
+            .expect("animegarden client");
+        AppState {
+            pool: SqlitePoolOptions::new()
+                .connect_lazy("sqlite::memory:")
+                .expect("sqlite pool"),
+            yuc: YucClient::new(&config.yuc).expect("yuc client"),
+            downloads: DownloadCoordinator::new(
+                Arc::new(PlanningDownloadEngine),
+                DownloadRuntimeSettings::new(1, 0, 0),
+                Some(bangumi.clone()),
+                parser.clone(),
+            ),
+            discovery: ResourceDiscoveryCoordinator::new(animegarden),
+            metrics: RuntimeMetrics::new("127.0.0.1:0".to_owned()),
+            login_guard: Arc::new(LoginAttemptGuard::new(&config.auth)),
+            subject_refreshes: Arc::default(),
+            bangumi,
+            parser,
+            config,
+        }
+    }
+
+    #[tokio::test]
+    async fn health_reports_starting_before_first_heartbeat() {
+        let response = health(State(sample_state())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("health body");
+        let body: Value = serde_json::from_slice(&body).expect("health json");
+        assert_eq!(body["data"]["status"], "starting");
+        assert_eq!(body["data"]["downloadWorker"], "starting");
+    }
+
+    #[tokio::test]
+    async fn duplicate_subject_refresh_answers_conflict() {
+        let state = sample_state();
+        state
+            .subject_refreshes
+            .lock()
+            .expect("refresh set")
+            .insert(42);
+
+        let error = admin_refresh_subject(
+            State(state.clone()),
+            AdminIdentity {
+                username: "admin".to_owned(),
+            },
+            Path(42),
+        )
+        .await
+        .expect_err("duplicate refresh is rejected");
+
+        assert_eq!(error.into_response().status(), StatusCode::CONFLICT);
+        assert!(
+            state
+                .subject_refreshes
+                .lock()
+                .expect("refresh set")
+                .contains(&42)
+        );
+    }
+
+    #[test]
+    fn attachment_disposition_keeps_ascii_fallback_and_encodes_utf8() {
+        assert_eq!(
+            attachment_disposition("[Sub] 葬送的芙莉莲 - 01.mkv"),
+            "attachment; filename=\"[Sub] ______ - 01.mkv\"; \
+             filename*=UTF-8''%5BSub%5D%20%E8%91%AC%E9%80%81%E7%9A%84%E8%8A%99%E8%8E%89%E8%8E%B2%20-%2001.mkv"
+        );
+    }
+
+    #[test]
+    fn completeness_counts_available_and_lists_aired_missing_episodes() {
+        let episodes = vec![
+            (sample_episode(1.0, "2020-01-01"), true, None),
+            (sample_episode(2.0, "2020-01-08"), false, None),
+            (sample_episode(3.0, "2999-01-01"), false, None),
+        ];
+
+        let completeness = summarize_completeness(Some(12), &episodes);
+        assert_eq!(completeness.total_episodes, Some(12));
+        assert_eq!(completeness.available_episodes, 1);
+        assert_eq!(completeness.missing_episode_numbers, vec![2.0]);
+
+        let fallback = summarize_completeness(None, &episodes);
+        assert_eq!(fallback.total_episodes, Some(3));
+        assert!(summarize_completeness(None, &[]).ratio.is_none());
+    }
+
     #[test]
     fn completed_collection_window_rejects_oversized_franchise_batches() {
         let targets = (1..=11).map(|value| value as f64).collect::<Vec<_>>();
@@ -3803,6 +4297,7 @@ mod tests {
             replaced_at: None,
             failed_at: None,
             last_indexed_at: None,
+            rescan_held_at: None,
         };
         let part_two_targets = (1..=12).map(|value| value as f64).collect::<Vec<_>>();
 
@@ -9,10 +9,12 @@ use chrono_tz::Tz;
 use futures::stream::{self, StreamExt};
 use regex::Regex;
 use sqlx::{FromRow, SqlitePool};
-use tracing::warn;
+use tracing::{Instrument, Span, debug_span, field, info, instrument, warn};
 
 use crate::{
-    bangumi::{BangumiClient, BangumiSearchQuery, EpisodeRaw, SubjectRaw},
+    bangumi::{AirDate, BangumiClient, BangumiSearchQuery, EpisodeRaw, SubjectRaw},
+    catalog_cache::{SUBJECT_UNAVAILABLE, mark_subject_unavailable},
+    matching::{self, normalize_title, score_text_pair},
     types::{AppError, CalendarDayDto, SubjectCardDto, WeekdayDto},
     yuc::YucClient,
 };
@@ -99,6 +101,7 @@ struct CalendarEntryRow {
     total_episodes: Option<i64>,
     rating_score: Option<f64>,
     release_status: Option<String>,
+    availability: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -160,6 +163,7 @@ impl CalendarEntryRow {
             total_episodes: self.total_episodes,
             rating_score: self.rating_score,
             catalog_label: None,
+            unavailable: self.availability.as_deref() == Some(SUBJECT_UNAVAILABLE),
         })
     }
 }
@@ -471,6 +475,7 @@ async fn populate_missing_matches(
     Ok(())
 }
 
+#[instrument(skip(pool, bangumi), fields(due = field::Empty))]
 async fn refresh_subject_statuses(
     pool: &SqlitePool,
     bangumi: &BangumiClient,
@@ -498,6 +503,7 @@ async fn refresh_subject_statuses(
         .map(|row| row.bangumi_subject_id)
         .collect::<Vec<_>>();
 
+    Span::current().record("due", subject_ids.len());
     if subject_ids.is_empty() {
         return Ok(());
     }
@@ -522,7 +528,15 @@ async fn refresh_subject_statuses(
 
                     let mut card = subject.to_card();
                     card.release_status = derive_release_status(&subject, &episodes).to_owned();
-                    Some(card)
+                    Some((subject_id, Some(card)))
+                }
+                Err(error @ AppError::NotFound(_)) => {
+                    warn!(
+                        subject_id,
+                        error = %error,
+                        "Bangumi subject is gone; marking the Yuc catalog entry unavailable"
+                    );
+                    Some((subject_id, None))
                 }
                 Err(error) => {
                     warn!(
@@ -534,15 +548,24 @@ async fn refresh_subject_statuses(
                 }
             }
         }
+        .instrument(debug_span!("refresh_subject_status", subject_id))
     }))
     .buffer_unordered(STATUS_REFRESH_CONCURRENCY)
     .filter_map(|item| async move { item })
     .collect::<Vec<_>>()
     .await;
 
-    for card in cards {
-        upsert_subject_cache(pool, &card, &refreshed_at, &refreshed_at).await?;
+    let refreshed = cards.len();
+    for (subject_id, card) in cards {
+        match card {
+            Some(card) => upsert_subject_cache(pool, &card, &refreshed_at, &refreshed_at).await?,
+            None => mark_subject_unavailable(pool, subject_id, &refreshed_at).await?,
+        }
     }
+    info!(
+        refreshed,
+        "Refreshed Bangumi subject statuses for Yuc catalog"
+    );
 
     Ok(())
 }
@@ -571,8 +594,9 @@ async fn upsert_subject_cache(
             rating_score,
             release_status,
             metadata_refreshed_at,
-            status_refreshed_at
-         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            status_refreshed_at,
+            availability
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, 'available')
          ON CONFLICT(bangumi_subject_id) DO UPDATE SET
             title = excluded.title,
             title_cn = excluded.title_cn,
@@ -586,7 +610,8 @@ async fn upsert_subject_cache(
             rating_score = excluded.rating_score,
             release_status = excluded.release_status,
             metadata_refreshed_at = excluded.metadata_refreshed_at,
-            status_refreshed_at = excluded.status_refreshed_at",
+            status_refreshed_at = excluded.status_refreshed_at,
+            availability = excluded.availability",
     )
     .bind(card.bangumi_subject_id)
     .bind(&card.title)
@@ -632,7 +657,8 @@ async fn load_calendar_rows(
             bangumi_subject_cache.tags_json,
             bangumi_subject_cache.total_episodes,
             bangumi_subject_cache.rating_score,
-            bangumi_subject_cache.release_status
+            bangumi_subject_cache.release_status,
+            bangumi_subject_cache.availability
          FROM yuc_catalog_entries
          INNER JOIN yuc_catalogs ON yuc_catalogs.id = yuc_catalog_entries.yuc_catalog_id
          LEFT JOIN bangumi_subject_cache
@@ -914,21 +940,11 @@ fn adjust_score_for_air_date(
     let Some(entry_date) = parse_catalog_air_date(entry) else {
         return base_score;
     };
-    let Some(subject_date) =
-        parse_subject_date(subject.air_date.as_ref().or(subject.date.as_ref()))
-    else {
+    let Some(subject_date) = subject.parsed_date() else {
         return base_score;
     };
 
-    let delta_days = (subject_date - entry_date).num_days().abs();
-    match delta_days {
-        0 => base_score + 84.0,
-        1..=3 => base_score + 56.0,
-        4..=10 => base_score + 28.0,
-        11..=21 => base_score + 8.0,
-        22..=45 => (base_score - 18.0).max(0.0),
-        _ => (base_score - 72.0).max(0.0),
-    }
+    matching::adjust_score_for_air_date(base_score, entry_date, subject_date)
 }
 
 fn parse_catalog_air_date(entry: &CatalogMatchRow) -> Option<NaiveDate> {
@@ -1194,90 +1210,6 @@ fn strip_variant(value: &str) -> String {
     normalize_title(&stripped)
 }
 
-fn normalize_title(value: &str) -> String {
-    value
-        .chars()
-        .filter(|character| character.is_alphanumeric())
-        .flat_map(char::to_lowercase)
-        .collect()
-}
-
-fn score_text_pair(
-    left_normalized: &str,
-    left_stripped: &str,
-    right_normalized: &str,
-    right_stripped: &str,
-) -> i32 {
-    let mut score = 0;
-
-    if !left_normalized.is_empty() && left_normalized == right_normalized {
-        score = score.max(140);
-    }
-
-    if !left_stripped.is_empty() && left_stripped == right_stripped {
-        score = score.max(136);
-    }
-
-    if !left_stripped.is_empty()
-        && !right_stripped.is_empty()
-        && (left_normalized.contains(right_stripped)
-            || right_normalized.contains(left_stripped)
-            || left_stripped.contains(right_stripped)
-            || right_stripped.contains(left_stripped))
-    {
-        score = score.max(108);
-    }
-
-    score = score.max((dice_coefficient(left_normalized, right_normalized) * 100.0).round() as i32);
-    score = score.max((dice_coefficient(left_stripped, right_stripped) * 112.0).round() as i32);
-
-    score
-}
-
-fn dice_coefficient(left: &str, right: &str) -> f32 {
-    if left.is_empty() || right.is_empty() {
-        return 0.0;
-    }
-    if left == right {
-        return 1.0;
-    }
-
-    let left_pairs = bigrams(left);
-    let right_pairs = bigrams(right);
-    if left_pairs.is_empty() || right_pairs.is_empty() {
-        return 0.0;
-    }
-
-    let mut overlap = 0usize;
-    let mut counts = HashMap::new();
-    for pair in &left_pairs {
-        *counts.entry(pair.clone()).or_insert(0usize) += 1;
-    }
-
-    for pair in &right_pairs {
-        if let Some(count) = counts.get_mut(pair) {
-            if *count > 0 {
-                *count -= 1;
-                overlap += 1;
-            }
-        }
-    }
-
-    (2 * overlap) as f32 / (left_pairs.len() + right_pairs.len()) as f32
-}
-
-fn bigrams(value: &str) -> Vec<String> {
-    let chars = value.chars().collect::<Vec<_>>();
-    if chars.len() < 2 {
-        return Vec::new();
-    }
-
-    chars
-        .windows(2)
-        .map(|window| window.iter().collect::<String>())
-        .collect()
-}
-
 fn sort_cards_by_broadcast_time(items: &mut [SubjectCardDto]) {
     items.sort_by(|left, right| {
         match (
@@ -1476,18 +1408,7 @@ fn status_refresh_due(value: Option<&str>) -> bool {
 }
 
 fn parse_episode_airdate(value: &str) -> Option<NaiveDate> {
-    let date_part = value
-        .trim()
-        .split_once('T')
-        .map(|(left, _)| left)
-        .unwrap_or(value)
-        .trim();
-
-    if date_part.is_empty() {
-        return None;
-    }
-
-    NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()
+    AirDate::parse(value)?.to_naive_date()
 }
 
 fn flatten_infobox_value(value: &serde_json::Value) -> String {
@@ -1609,6 +1530,7 @@ mod tests {
     };
     use crate::bangumi::{EpisodeRaw, SubjectRaw};
     use crate::bangumi::{ImageSetRaw, InfoboxRaw, RatingRaw, TagRaw};
+    use crate::types::SubjectType;
     use chrono_tz::Australia::Brisbane;
     use serde_json::json;
     #[test]
@@ -1682,6 +1604,7 @@ mod tests {
     fn sample_subject(total_episodes: Option<i64>, air_date: Option<String>) -> SubjectRaw {
         SubjectRaw {
             id: 1,
+            subject_type: Some(SubjectType::Anime),
             name: "Sample".to_owned(),
             name_cn: "Sample".to_owned(),
             summary: String::new(),
@@ -1809,6 +1732,7 @@ mod tests {
     ) -> SubjectRaw {
         SubjectRaw {
             id,
+            subject_type: Some(SubjectType::Anime),
             name: name.to_owned(),
             name_cn: name_cn.to_owned(),
             summary: String::new(),
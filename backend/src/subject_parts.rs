@@ -1,11 +1,11 @@
 use std::collections::HashSet;
 
-use anicargo_metadata_parser::parse_release_name;
+use anicargo_metadata_parser::Parser;
 
 use crate::{
     bangumi::{BangumiClient, SubjectRaw},
     discovery::{infer_part_hint_from_texts, infer_season_hint_from_texts},
-    types::AppError,
+    types::{AppError, SubjectType},
 };
 
 #[derive(Debug, Clone)]
@@ -31,12 +31,12 @@ struct SubjectIdentity {
     total_episodes: i64,
 }
 
-pub fn collect_base_title_aliases(title: &str, title_cn: &str) -> Vec<String> {
+pub fn collect_base_title_aliases(parser: &Parser, title: &str, title_cn: &str) -> Vec<String> {
     let mut values = Vec::new();
     let mut seen = HashSet::new();
 
     for value in [title, title_cn] {
-        for alias in extract_base_titles(value) {
+        for alias in extract_base_titles(parser, value) {
             let normalized = alias.trim();
             if normalized.is_empty() {
                 continue;
@@ -53,15 +53,16 @@ pub fn collect_base_title_aliases(title: &str, title_cn: &str) -> Vec<String> {
 
 pub async fn resolve_subject_part_group(
     bangumi: &BangumiClient,
+    parser: &Parser,
     subject_id: i64,
 ) -> Result<Option<SubjectPartGroup>, AppError> {
     let current = bangumi.fetch_subject(subject_id).await?;
-    let current_identity = SubjectIdentity::from_subject(&current);
+    let current_identity = SubjectIdentity::from_subject(parser, &current);
     let related = bangumi.fetch_related_subjects(subject_id).await?;
 
     let mut identities = vec![current_identity.clone()];
     for item in related {
-        if item.r#type != 2 {
+        if item.subject_type != SubjectType::Anime {
             continue;
         }
         if !matches!(
@@ -84,7 +85,7 @@ pub async fn resolve_subject_part_group(
                 continue;
             }
         };
-        let identity = SubjectIdentity::from_subject(&subject);
+        let identity = SubjectIdentity::from_subject(parser, &subject);
         if looks_like_split_part_peer(&current_identity, &identity) {
             identities.push(identity);
         }
@@ -219,10 +220,10 @@ fn looks_like_split_part_peer(current: &SubjectIdentity, candidate: &SubjectIden
 }
 
 impl SubjectIdentity {
-    fn from_subject(subject: &SubjectRaw) -> Self {
+    fn from_subject(parser: &Parser, subject: &SubjectRaw) -> Self {
         Self {
             bangumi_subject_id: subject.id,
-            base_titles: collect_base_title_aliases(&subject.name, &subject.name_cn),
+            base_titles: collect_base_title_aliases(parser, &subject.name, &subject.name_cn),
             season_hint: infer_season_hint_from_texts([subject.name.as_str(), subject.name_cn.as_str()]),
             part_hint: infer_part_hint_from_texts([subject.name.as_str(), subject.name_cn.as_str()]),
             total_episodes: subject.total_episodes.unwrap_or_default(),
@@ -230,13 +231,13 @@ impl SubjectIdentity {
     }
 }
 
-fn extract_base_titles(value: &str) -> Vec<String> {
+fn extract_base_titles(parser: &Parser, value: &str) -> Vec<String> {
     let normalized = value.trim();
     if normalized.is_empty() {
         return Vec::new();
     }
 
-    let parsed = parse_release_name(normalized);
+    let parsed = parser.parse_release_name(normalized);
     let mut values = Vec::new();
     for candidate in [
         parsed.titles.primary.as_deref(),
@@ -290,10 +291,12 @@ mod tests {
         SubjectPartGroup, SubjectPartSegment, collect_base_title_aliases, first_segment,
         last_segment, map_global_episode_to_segment, map_global_range_to_segments,
     };
+    use anicargo_metadata_parser::Parser;
 
     #[test]
     fn strips_part_markers_from_subject_aliases() {
         let aliases = collect_base_title_aliases(
+            &Parser::default(),
             "关于我转生变成史莱姆这档事 第二季 第2部分",
             "転生したらスライムだった件 第2期 2",
         );
@@ -61,9 +61,13 @@ impl RuntimeMetrics {
         })
     }
 
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
     pub fn snapshot(&self) -> RuntimeSnapshot {
         RuntimeSnapshot {
-            uptime: self.started_at.elapsed(),
+            uptime: self.uptime(),
             server_address: self.server_address.clone(),
             request_total: self.request_total.load(Ordering::Relaxed),
             request_failures: self.request_failures.load(Ordering::Relaxed),
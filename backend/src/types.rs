@@ -1,9 +1,11 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 use crate::bangumi::SearchFacets;
 
@@ -19,7 +21,7 @@ impl<T> ApiEnvelope<T> {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 struct ErrorEnvelope {
     code: String,
@@ -35,6 +37,13 @@ pub enum AppError {
     #[error("{0}")]
     NotFound(String),
     #[error("{0}")]
+    Conflict(String),
+    #[error("{message}")]
+    TooManyRequests {
+        message: String,
+        retry_after_secs: u64,
+    },
+    #[error("{0}")]
     Upstream(String),
     #[error("{0}")]
     Internal(String),
@@ -53,6 +62,17 @@ impl AppError {
         Self::NotFound(message.into())
     }
 
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::Conflict(message.into())
+    }
+
+    pub fn too_many_requests(message: impl Into<String>, retry_after_secs: u64) -> Self {
+        Self::TooManyRequests {
+            message: message.into(),
+            retry_after_secs,
+        }
+    }
+
     pub fn upstream(message: impl Into<String>) -> Self {
         Self::Upstream(message.into())
     }
@@ -68,6 +88,8 @@ impl IntoResponse for AppError {
             Self::BadRequest(_) => (StatusCode::BAD_REQUEST, "bad_request"),
             Self::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "unauthorized"),
             Self::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+            Self::Conflict(_) => (StatusCode::CONFLICT, "conflict"),
+            Self::TooManyRequests { .. } => (StatusCode::TOO_MANY_REQUESTS, "too_many_requests"),
             Self::Upstream(_) => (StatusCode::BAD_GATEWAY, "upstream_error"),
             Self::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
         };
@@ -77,17 +99,47 @@ impl IntoResponse for AppError {
             message: self.to_string(),
         });
 
+        if let Self::TooManyRequests {
+            retry_after_secs, ..
+        } = self
+        {
+            return (
+                status,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                body,
+            )
+                .into_response();
+        }
+
         (status, body).into_response()
     }
 }
 
-#[derive(Debug, Serialize)]
+/// Documents errors as the envelope `into_response` writes.
+impl JsonSchema for AppError {
+    fn schema_name() -> Cow<'static, str> {
+        ErrorEnvelope::schema_name()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        ErrorEnvelope::schema_id()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        ErrorEnvelope::json_schema(generator)
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct HealthResponse {
     pub status: String,
+    pub download_worker: String,
+    pub version: String,
+    pub git_sha: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct BootstrapResponse {
     pub device_id: String,
@@ -96,7 +148,7 @@ pub struct BootstrapResponse {
     pub policy: PolicyDto,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ViewerSummary {
     pub kind: String,
@@ -128,13 +180,13 @@ impl ViewerSummary {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CalendarResponse {
     pub days: Vec<CalendarDayDto>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduleDisplayQuery {
     #[serde(default)]
@@ -143,14 +195,14 @@ pub struct ScheduleDisplayQuery {
     pub deep_night_mode: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CalendarDayDto {
     pub weekday: WeekdayDto,
     pub items: Vec<SubjectCardDto>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WeekdayDto {
     pub id: u8,
@@ -159,7 +211,7 @@ pub struct WeekdayDto {
     pub ja: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SubjectCardDto {
     pub bangumi_subject_id: i64,
@@ -176,16 +228,17 @@ pub struct SubjectCardDto {
     pub total_episodes: Option<i64>,
     pub rating_score: Option<f64>,
     pub catalog_label: Option<String>,
+    pub unavailable: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CatalogManifestResponse {
     pub preview_available: bool,
     pub special_available: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CatalogSectionDto {
     pub key: String,
@@ -193,7 +246,7 @@ pub struct CatalogSectionDto {
     pub items: Vec<SubjectCardDto>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CatalogPageResponse {
     pub kind: String,
@@ -201,7 +254,7 @@ pub struct CatalogPageResponse {
     pub sections: Vec<CatalogSectionDto>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchRequest {
     #[serde(default)]
@@ -236,7 +289,7 @@ pub struct SearchRequest {
     pub page_size: Option<usize>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResponse {
     pub items: Vec<SubjectCardDto>,
@@ -247,7 +300,7 @@ pub struct SearchResponse {
     pub has_next_page: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SubjectDetailResponse {
     pub subject: SubjectDetailDto,
@@ -256,10 +309,88 @@ pub struct SubjectDetailResponse {
     pub download_status: Option<SubjectDownloadStatusDto>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Bangumi subject category. The numeric codes match the Bangumi API and are
+/// what gets stored; the API surface uses the lowercase names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(from = "i64")]
+pub enum SubjectType {
+    Book,
+    Anime,
+    Music,
+    Game,
+    Real,
+    Unknown(i64),
+}
+
+impl SubjectType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Book => "book",
+            Self::Anime => "anime",
+            Self::Music => "music",
+            Self::Game => "game",
+            Self::Real => "real",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+}
+
+impl From<i64> for SubjectType {
+    fn from(value: i64) -> Self {
+        match value {
+            1 => Self::Book,
+            2 => Self::Anime,
+            3 => Self::Music,
+            4 => Self::Game,
+            6 => Self::Real,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<SubjectType> for i64 {
+    fn from(value: SubjectType) -> Self {
+        match value {
+            SubjectType::Book => 1,
+            SubjectType::Anime => 2,
+            SubjectType::Music => 3,
+            SubjectType::Game => 4,
+            SubjectType::Real => 6,
+            SubjectType::Unknown(other) => other,
+        }
+    }
+}
+
+impl Serialize for SubjectType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl JsonSchema for SubjectType {
+    fn schema_name() -> Cow<'static, str> {
+        "SubjectType".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        let names = [
+            Self::Book,
+            Self::Anime,
+            Self::Music,
+            Self::Game,
+            Self::Real,
+            Self::Unknown(0),
+        ]
+        .map(Self::as_str);
+        json_schema!({ "type": "string", "enum": names })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SubjectDetailDto {
     pub bangumi_subject_id: i64,
+    pub subject_type: Option<SubjectType>,
     pub title: String,
     pub title_cn: String,
     pub summary: String,
@@ -278,14 +409,72 @@ pub struct SubjectDetailDto {
     pub related_subjects: Vec<SubjectCardDto>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectEpisodesResponse {
+    pub bangumi_subject_id: i64,
+    pub episodes: Vec<EpisodeDto>,
+    pub completeness: SubjectCompletenessDto,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectPersonsResponse {
+    pub bangumi_subject_id: i64,
+    pub items: Vec<SubjectPersonDto>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectPersonDto {
+    pub bangumi_person_id: i64,
+    pub name: String,
+    pub relation: String,
+    pub career: Vec<String>,
+    pub image_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectCharactersResponse {
+    pub bangumi_subject_id: i64,
+    pub items: Vec<SubjectCharacterDto>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectCharacterDto {
+    pub bangumi_character_id: i64,
+    pub name: String,
+    pub relation: String,
+    pub image_url: Option<String>,
+    pub actors: Vec<SubjectCastActorDto>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectCastActorDto {
+    pub bangumi_person_id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectCompletenessDto {
+    pub total_episodes: Option<i64>,
+    pub available_episodes: i64,
+    pub ratio: Option<f64>,
+    pub missing_episode_numbers: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct InfoboxItemDto {
     pub key: String,
     pub value: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EpisodeDto {
     pub bangumi_episode_id: i64,
@@ -299,7 +488,7 @@ pub struct EpisodeDto {
     pub availability_note: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionStateDto {
     pub is_subscribed: bool,
@@ -308,7 +497,7 @@ pub struct SubscriptionStateDto {
     pub source: ViewerSummary,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SubjectDownloadStatusDto {
     pub bangumi_subject_id: i64,
@@ -336,7 +525,7 @@ pub struct SubjectDownloadStatusDto {
     pub last_evaluated_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ToggleSubscriptionResponse {
     pub bangumi_subject_id: i64,
@@ -344,7 +533,7 @@ pub struct ToggleSubscriptionResponse {
     pub download: DownloadDecisionDto,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadDecisionDto {
     pub demand_state: String,
@@ -352,7 +541,7 @@ pub struct DownloadDecisionDto {
     pub job: Option<DownloadJobDto>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadJobDto {
     pub id: i64,
@@ -375,13 +564,17 @@ pub struct DownloadJobDto {
     pub updated_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminDownloadQueueResponse {
     pub items: Vec<DownloadJobDto>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub has_next_page: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadExecutionDto {
     pub id: i64,
@@ -414,9 +607,10 @@ pub struct DownloadExecutionDto {
     pub replaced_at: Option<String>,
     pub failed_at: Option<String>,
     pub last_indexed_at: Option<String>,
+    pub rescan_held_at: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadExecutionEventDto {
     pub id: i64,
@@ -432,7 +626,7 @@ pub struct DownloadExecutionEventDto {
     pub created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadExecutionDecisionDto {
     pub reason: String,
@@ -440,35 +634,49 @@ pub struct DownloadExecutionDecisionDto {
     pub replaced_execution_id: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminDownloadExecutionsResponse {
     pub download_job_id: i64,
     pub items: Vec<DownloadExecutionDto>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminDownloadExecutionEventsResponse {
     pub download_execution_id: i64,
     pub items: Vec<DownloadExecutionEventDto>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminExecutionReindexResponse {
+    pub download_execution_id: i64,
+    pub media_count: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSubjectRefreshResponse {
+    pub card: SubjectCardDto,
+    pub episode_count: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ForceDownloadResponse {
     pub bangumi_subject_id: i64,
     pub decision: DownloadDecisionDto,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ActivateDownloadResponse {
     pub download_job_id: i64,
     pub decision: DownloadExecutionDecisionDto,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceCandidateDto {
     pub id: i64,
@@ -498,14 +706,33 @@ pub struct ResourceCandidateDto {
     pub discovered_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminDownloadCandidatesResponse {
     pub download_job_id: i64,
     pub items: Vec<ResourceCandidateDto>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectLookupRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminDownloadQueueRequest {
+    #[serde(default)]
+    pub lifecycle: Option<String>,
+    #[serde(default)]
+    pub trigger_kind: Option<String>,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceLibraryRequest {
     #[serde(default)]
@@ -516,7 +743,7 @@ pub struct ResourceLibraryRequest {
     pub page_size: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SubjectCollectionRequest {
     #[serde(default)]
@@ -529,7 +756,7 @@ pub struct SubjectCollectionRequest {
     pub page_size: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceLibraryItemDto {
     pub id: i64,
@@ -553,7 +780,7 @@ pub struct ResourceLibraryItemDto {
     pub updated_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceLibraryResponse {
     pub items: Vec<ResourceLibraryItemDto>,
@@ -564,7 +791,7 @@ pub struct ResourceLibraryResponse {
     pub has_next_page: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SubjectCollectionResponse {
     pub items: Vec<SubjectCardDto>,
@@ -574,7 +801,7 @@ pub struct SubjectCollectionResponse {
     pub has_next_page: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ActiveDownloadDto {
     pub bangumi_subject_id: i64,
@@ -597,13 +824,13 @@ pub struct ActiveDownloadDto {
     pub updated_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ActiveDownloadsResponse {
     pub items: Vec<ActiveDownloadDto>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EpisodeSubtitleTrackDto {
     pub id: String,
@@ -613,7 +840,7 @@ pub struct EpisodeSubtitleTrackDto {
     pub url: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EpisodePlaybackMediaDto {
     pub media_inventory_id: i64,
@@ -627,7 +854,7 @@ pub struct EpisodePlaybackMediaDto {
     pub subtitle_tracks: Vec<EpisodeSubtitleTrackDto>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EpisodePlaybackResponse {
     pub bangumi_subject_id: i64,
@@ -638,7 +865,7 @@ pub struct EpisodePlaybackResponse {
     pub media: Option<EpisodePlaybackMediaDto>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaybackHistoryRecordRequest {
     pub bangumi_subject_id: i64,
@@ -646,7 +873,7 @@ pub struct PlaybackHistoryRecordRequest {
     pub media_inventory_id: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaybackHistoryItemDto {
     pub bangumi_subject_id: i64,
@@ -663,7 +890,7 @@ pub struct PlaybackHistoryItemDto {
     pub play_count: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaybackHistoryResponse {
     pub items: Vec<PlaybackHistoryItemDto>,
@@ -673,28 +900,28 @@ pub struct PlaybackHistoryResponse {
     pub has_next_page: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CredentialsRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthResponse {
     pub token: String,
     pub viewer: ViewerSummary,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminAuthResponse {
     pub token: String,
     pub admin_username: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PolicyDto {
     pub subscription_threshold: i64,
@@ -705,7 +932,7 @@ pub struct PolicyDto {
     pub download_limit_mb: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct FansubRuleDto {
     pub id: i64,
@@ -715,7 +942,7 @@ pub struct FansubRuleDto {
     pub is_blacklist: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminCountsDto {
     pub devices: i64,
@@ -724,7 +951,7 @@ pub struct AdminCountsDto {
     pub fansub_rules: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminDashboardResponse {
     pub admin_username: String,
@@ -733,7 +960,7 @@ pub struct AdminDashboardResponse {
     pub counts: AdminCountsDto,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeHttpStatsDto {
     pub active_requests: u64,
@@ -746,7 +973,7 @@ pub struct RuntimeHttpStatsDto {
     pub last_latency_ms: u64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeOverviewDto {
     pub devices: i64,
@@ -765,7 +992,7 @@ pub struct RuntimeOverviewDto {
     pub peer_count: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminRuntimeResponse {
     pub server_address: String,
@@ -777,7 +1004,7 @@ pub struct AdminRuntimeResponse {
     pub runtime: RuntimeOverviewDto,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdatePolicyRequest {
     pub subscription_threshold: i64,
@@ -788,7 +1015,7 @@ pub struct UpdatePolicyRequest {
     pub download_limit_mb: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpsertFansubRuleRequest {
     pub fansub_name: String,
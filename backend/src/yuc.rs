@@ -11,6 +11,7 @@ use reqwest::Client;
 
 use crate::{
     config::YucConfig,
+    matching::{normalize_title, score_text_pair},
     types::{AppError, CatalogSectionDto, SubjectCardDto, SubjectDetailDto},
 };
 
@@ -472,38 +473,6 @@ fn score_entry(entry: &YucScheduleEntry, targets: &[MatchTarget]) -> i32 {
         .unwrap_or_default()
 }
 
-fn score_text_pair(
-    left_normalized: &str,
-    left_stripped: &str,
-    right_normalized: &str,
-    right_stripped: &str,
-) -> i32 {
-    let mut score = 0;
-
-    if !left_normalized.is_empty() && left_normalized == right_normalized {
-        score = score.max(140);
-    }
-
-    if !left_stripped.is_empty() && left_stripped == right_stripped {
-        score = score.max(136);
-    }
-
-    if !left_stripped.is_empty()
-        && !right_stripped.is_empty()
-        && (left_normalized.contains(right_stripped)
-            || right_normalized.contains(left_stripped)
-            || left_stripped.contains(right_stripped)
-            || right_stripped.contains(left_stripped))
-    {
-        score = score.max(108);
-    }
-
-    score = score.max((dice_coefficient(left_normalized, right_normalized) * 100.0).round() as i32);
-    score = score.max((dice_coefficient(left_stripped, right_stripped) * 112.0).round() as i32);
-
-    score
-}
-
 fn build_match_targets(title: &str, title_cn: &str) -> Vec<MatchTarget> {
     let mut targets = Vec::new();
 
@@ -692,6 +661,7 @@ fn parse_future_season_sections(html: &str, title: &str) -> Vec<CatalogSectionDt
                 total_episodes: None,
                 rating_score: None,
                 catalog_label,
+                unavailable: false,
             })
         })
         .collect::<Vec<_>>();
@@ -800,6 +770,7 @@ fn build_catalog_card(
         total_episodes: None,
         rating_score: None,
         catalog_label,
+        unavailable: false,
     }
 }
 
@@ -924,60 +895,6 @@ fn strip_variant(value: &str) -> String {
     normalize_title(&stripped)
 }
 
-fn normalize_title(value: &str) -> String {
-    value
-        .chars()
-        .filter(|character| character.is_alphanumeric())
-        .flat_map(char::to_lowercase)
-        .collect()
-}
-
-fn dice_coefficient(left: &str, right: &str) -> f32 {
-    if left.is_empty() || right.is_empty() {
-        return 0.0;
-    }
-
-    if left == right {
-        return 1.0;
-    }
-
-    let left_pairs = bigrams(left);
-    let right_pairs = bigrams(right);
-
-    if left_pairs.is_empty() || right_pairs.is_empty() {
-        return 0.0;
-    }
-
-    let mut overlap = 0usize;
-    let mut counts = HashMap::new();
-    for pair in &left_pairs {
-        *counts.entry(pair.clone()).or_insert(0usize) += 1;
-    }
-
-    for pair in &right_pairs {
-        if let Some(count) = counts.get_mut(pair) {
-            if *count > 0 {
-                *count -= 1;
-                overlap += 1;
-            }
-        }
-    }
-
-    (2 * overlap) as f32 / (left_pairs.len() + right_pairs.len()) as f32
-}
-
-fn bigrams(value: &str) -> Vec<String> {
-    let chars = value.chars().collect::<Vec<_>>();
-    if chars.len() < 2 {
-        return Vec::new();
-    }
-
-    chars
-        .windows(2)
-        .map(|window| window.iter().collect::<String>())
-        .collect()
-}
-
 fn schedule_card_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
     REGEX.get_or_init(|| {
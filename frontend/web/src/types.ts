@@ -321,6 +321,7 @@ export type DownloadExecution = {
   replacedAt: string | null;
   failedAt: string | null;
   lastIndexedAt: string | null;
+  rescanHeldAt: string | null;
 };
 
 export type DownloadExecutionEvent = {
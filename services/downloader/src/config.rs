@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Parser)]
 #[command(
     name = "anicargo-downloader",
+    version,
     about = "Standalone torrent downloader service for Anicargo"
 )]
 pub struct DownloaderCli {
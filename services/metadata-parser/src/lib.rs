@@ -1,9 +1,9 @@
 mod parser;
 mod types;
 
-pub use parser::{parse_file_name, parse_release_name};
+pub use parser::{Parser, parse_file_name, parse_release_name};
 pub use types::{
     AudioInfo, EpisodeDescriptor, EpisodeNumber, EpisodeRangeDescriptor, FansubInfo, FileInfo,
-    FileRole, ParseFlags, ParseResult, ParseSourceKind, ScriptKind, SeasonInfo, SubtitleInfo,
-    SubtitleStorage, TechnicalInfo, TitleCandidate, TitleInfo,
+    FileRole, ParseFlags, ParseOptions, ParseQuality, ParseResult, ParseSourceKind, ScriptKind,
+    SeasonInfo, SubtitleInfo, SubtitleStorage, TechnicalInfo, TitleCandidate, TitleInfo,
 };
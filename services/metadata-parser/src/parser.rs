@@ -4,19 +4,56 @@ use regex::Regex;
 
 use crate::types::{
     AudioInfo, EpisodeDescriptor, EpisodeNumber, EpisodeRangeDescriptor, FansubInfo, FileInfo,
-    FileRole, ParseFlags, ParseResult, ParseSourceKind, ScriptKind, SeasonInfo, SubtitleInfo,
-    SubtitleStorage, TechnicalInfo, TitleCandidate, TitleInfo,
+    FileRole, ParseFlags, ParseOptions, ParseQuality, ParseResult, ParseSourceKind, ScriptKind,
+    SeasonInfo, SubtitleInfo, SubtitleStorage, TechnicalInfo, TitleCandidate, TitleInfo,
 };
 
+/// A parser bound to one set of [`ParseOptions`], built once and reused for every input.
+///
+/// Title strip patterns are compiled when the parser is built, so a bad pattern is
+/// reported up front instead of being skipped on every parse.
+#[derive(Debug, Clone, Default)]
+pub struct Parser {
+    ignored_tokens: Vec<String>,
+    title_strip_patterns: Vec<Regex>,
+}
+
+impl Parser {
+    pub fn new(options: ParseOptions) -> Result<Self, regex::Error> {
+        let title_strip_patterns = options
+            .title_strip_patterns
+            .iter()
+            .map(|pattern| pattern.trim())
+            .filter(|pattern| !pattern.is_empty())
+            .map(Regex::new)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            ignored_tokens: options.ignored_tokens,
+            title_strip_patterns,
+        })
+    }
+
+    pub fn parse_release_name(&self, input: &str) -> ParseResult {
+        parse_impl(input, ParseSourceKind::ReleaseTitle, self)
+    }
+
+    pub fn parse_file_name(&self, input: &str) -> ParseResult {
+        parse_impl(input, ParseSourceKind::FileName, self)
+    }
+}
+
+/// Parses with default options; use a [`Parser`] to apply [`ParseOptions`].
 pub fn parse_release_name(input: &str) -> ParseResult {
-    parse_impl(input, ParseSourceKind::ReleaseTitle)
+    parse_impl(input, ParseSourceKind::ReleaseTitle, &Parser::default())
 }
 
+/// Parses with default options; use a [`Parser`] to apply [`ParseOptions`].
 pub fn parse_file_name(input: &str) -> ParseResult {
-    parse_impl(input, ParseSourceKind::FileName)
+    parse_impl(input, ParseSourceKind::FileName, &Parser::default())
 }
 
-fn parse_impl(input: &str, source_kind: ParseSourceKind) -> ParseResult {
+fn parse_impl(input: &str, source_kind: ParseSourceKind, parser: &Parser) -> ParseResult {
     let raw = input.trim().to_owned();
     let file_name = match source_kind {
         ParseSourceKind::FileName => basename(&raw),
@@ -25,7 +62,7 @@ fn parse_impl(input: &str, source_kind: ParseSourceKind) -> ParseResult {
     let (stem, extension) = split_extension(&file_name);
     let role = extension.as_deref().map(detect_file_role);
 
-    let mut body = stem.clone();
+    let mut body = strip_ignored_tokens(&stem, &parser.ignored_tokens);
     let fansub = extract_leading_fansub(&mut body);
     let bracket_tokens = extract_enclosed_tokens(&body);
 
@@ -65,7 +102,8 @@ fn parse_impl(input: &str, source_kind: ParseSourceKind) -> ParseResult {
         parse_dual_episode_alias(&body)
     };
     let title_body = cleanup_title_body(&body, episode_range.as_ref(), episode.as_ref());
-    let titles = parse_titles(&title_body, season.as_ref());
+    let mut titles = parse_titles(&title_body, season.as_ref());
+    apply_title_strip_patterns(&mut titles, &parser.title_strip_patterns);
 
     for token in bracket_tokens {
         if is_known_tag(&token) || parse_episode_range_token(&token).is_some() || token.is_empty() {
@@ -74,6 +112,8 @@ fn parse_impl(input: &str, source_kind: ParseSourceKind) -> ParseResult {
         unparsed.push(token);
     }
 
+    let quality = assess_quality(&titles, &fansub, episode.as_ref(), episode_range.as_ref());
+
     ParseResult {
         source_kind,
         raw,
@@ -92,11 +132,44 @@ fn parse_impl(input: &str, source_kind: ParseSourceKind) -> ParseResult {
         technical,
         audio,
         flags,
+        quality,
         raw_tags,
         unparsed,
     }
 }
 
+fn assess_quality(
+    titles: &TitleInfo,
+    fansub: &FansubInfo,
+    episode: Option<&EpisodeDescriptor>,
+    episode_range: Option<&EpisodeRangeDescriptor>,
+) -> ParseQuality {
+    let has_title = titles
+        .primary
+        .as_deref()
+        .is_some_and(|title| title.chars().any(char::is_alphabetic));
+    let has_episode = episode.is_some() || episode_range.is_some();
+    let has_fansub = fansub.primary.is_some();
+
+    let mut score = 0;
+    if has_title {
+        score += 50;
+    }
+    if has_episode {
+        score += 40;
+    }
+    if has_fansub {
+        score += 10;
+    }
+
+    ParseQuality {
+        has_title,
+        has_episode,
+        has_fansub,
+        score,
+    }
+}
+
 fn basename(value: &str) -> String {
     value
         .rsplit(['/', '\\'])
@@ -106,6 +179,56 @@ fn basename(value: &str) -> String {
         .to_owned()
 }
 
+fn strip_ignored_tokens(value: &str, ignored_tokens: &[String]) -> String {
+    let mut cleaned = value.to_owned();
+    let mut changed = false;
+
+    for token in ignored_tokens
+        .iter()
+        .map(|token| token.trim())
+        .filter(|token| !token.is_empty())
+    {
+        let needle = token.to_ascii_lowercase();
+        while let Some(index) = cleaned.to_ascii_lowercase().find(&needle) {
+            cleaned.replace_range(index..index + needle.len(), " ");
+            changed = true;
+        }
+    }
+
+    if changed {
+        collapse_spaces(&cleaned)
+    } else {
+        cleaned
+    }
+}
+
+fn apply_title_strip_patterns(titles: &mut TitleInfo, rules: &[Regex]) {
+    if rules.is_empty() {
+        return;
+    }
+
+    let original = titles.primary.clone();
+    for slot in [
+        &mut titles.primary,
+        &mut titles.cjk,
+        &mut titles.latin,
+        &mut titles.japanese,
+    ] {
+        if let Some(value) = slot.as_deref() {
+            let mut cleaned = value.to_owned();
+            for rule in rules {
+                cleaned = rule.replace_all(&cleaned, " ").into_owned();
+            }
+            let cleaned = collapse_spaces(&cleaned);
+            *slot = (!cleaned.is_empty()).then_some(cleaned);
+        }
+    }
+
+    if titles.primary != original {
+        titles.original_primary = original;
+    }
+}
+
 fn split_extension(file_name: &str) -> (String, Option<String>) {
     let lower = file_name.to_ascii_lowercase();
     if let Some(index) = lower.rfind('.') {
@@ -905,8 +1028,10 @@ fn part_regexes() -> &'static [Regex] {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_file_name, parse_release_name};
-    use crate::types::{FileRole, ParseResult, ParseSourceKind, ScriptKind, SubtitleStorage};
+    use super::{Parser, parse_file_name, parse_release_name};
+    use crate::types::{
+        FileRole, ParseOptions, ParseResult, ParseSourceKind, ScriptKind, SubtitleStorage,
+    };
 
     fn print_case(name: &str, parsed: &ParseResult) {
         println!("===== {name} =====");
@@ -1093,4 +1218,73 @@ mod tests {
                 .any(|candidate| candidate.script == ScriptKind::Japanese)
         );
     }
+
+    #[test]
+    fn ignored_tokens_are_removed_before_parsing() {
+        let options = ParseOptions {
+            ignored_tokens: vec!["[TrackerBot]".to_owned()],
+            ..ParseOptions::default()
+        };
+        let parsed = Parser::new(options).expect("parser").parse_release_name(
+            "[trackerbot][LoliHouse] Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC]",
+        );
+        print_case("ignored_tokens_are_removed_before_parsing", &parsed);
+
+        assert_eq!(parsed.fansub.primary.as_deref(), Some("LoliHouse"));
+        assert_eq!(parsed.titles.primary.as_deref(), Some("Sousou no Frieren"));
+        assert_eq!(parsed.episode.map(|item| item.primary.major), Some(5));
+    }
+
+    #[test]
+    fn invalid_title_strip_pattern_is_rejected_when_building_parser() {
+        let options = ParseOptions {
+            title_strip_patterns: vec!["(".to_owned()],
+            ..ParseOptions::default()
+        };
+
+        assert!(Parser::new(options).is_err());
+    }
+
+    #[test]
+    fn title_strip_patterns_clean_title_and_keep_original() {
+        let options = ParseOptions {
+            title_strip_patterns: vec![r"(?i)^airing\s+".to_owned()],
+            ..ParseOptions::default()
+        };
+        let parsed = Parser::new(options).expect("parser").parse_release_name(
+            "[LoliHouse] AIRING Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC]",
+        );
+        print_case(
+            "title_strip_patterns_clean_title_and_keep_original",
+            &parsed,
+        );
+
+        assert_eq!(parsed.titles.primary.as_deref(), Some("Sousou no Frieren"));
+        assert_eq!(
+            parsed.titles.original_primary.as_deref(),
+            Some("AIRING Sousou no Frieren")
+        );
+        assert_eq!(parsed.episode.map(|item| item.primary.major), Some(5));
+    }
+
+    #[test]
+    fn tag_only_file_name_reports_low_quality() {
+        let parsed = parse_file_name("[VCB-Studio][1080p][HEVC].mkv");
+        print_case("tag_only_file_name_reports_low_quality", &parsed);
+
+        assert_eq!(parsed.file.role, Some(FileRole::Video));
+        assert!(!parsed.quality.has_title);
+        assert!(!parsed.quality.has_episode);
+        assert!(parsed.quality.score < 50);
+    }
+
+    #[test]
+    fn complete_release_name_reports_full_quality() {
+        let parsed =
+            parse_release_name("[LoliHouse] Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC]");
+
+        assert!(parsed.quality.has_title);
+        assert!(parsed.quality.has_episode);
+        assert_eq!(parsed.quality.score, 100);
+    }
 }
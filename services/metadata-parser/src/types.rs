@@ -7,6 +7,14 @@ pub enum ParseSourceKind {
     FileName,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ParseOptions {
+    #[serde(default)]
+    pub ignored_tokens: Vec<String>,
+    #[serde(default)]
+    pub title_strip_patterns: Vec<String>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FileRole {
@@ -57,6 +65,8 @@ pub struct TitleInfo {
     pub japanese: Option<String>,
     #[serde(default)]
     pub alternates: Vec<TitleCandidate>,
+    #[serde(default)]
+    pub original_primary: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -131,6 +141,14 @@ pub struct ParseFlags {
     pub is_complete: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ParseQuality {
+    pub has_title: bool,
+    pub has_episode: bool,
+    pub has_fansub: bool,
+    pub score: u8,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ParseResult {
     pub source_kind: ParseSourceKind,
@@ -147,6 +165,8 @@ pub struct ParseResult {
     pub audio: AudioInfo,
     pub flags: ParseFlags,
     #[serde(default)]
+    pub quality: ParseQuality,
+    #[serde(default)]
     pub raw_tags: Vec<String>,
     #[serde(default)]
     pub unparsed: Vec<String>,